@@ -0,0 +1,354 @@
+use crate::{
+    theme::Role,
+    utils::repeated_char,
+    visualizer::{CharVisualizationOption, Visualizer},
+};
+
+/// Bars drawn with it get eighth-of-a-cell precision at their ends
+pub const FULL_BLOCK: char = '█';
+/// Bar chars that are always drawn whole cells
+pub const SHADED_BLOCK: char = '▓';
+pub const LIGHT_BLOCK: char = '▒';
+
+pub const PLOT_CHAR: char = '*';
+
+const EIGHTHS_UP: [char; 8] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇'];
+const EIGHTHS_RIGHT: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Sets the color of bars drawn with the char, or of the line plots for `PLOT_CHAR`.
+/// Colors of the visualization are per char, so a full block bar color applies to the partial
+/// blocks at its end too; only one color can use full blocks
+pub fn set_bar_color(vis: &mut dyn Visualizer, bar_char: char, role: Role) {
+    let chars = if bar_char == FULL_BLOCK {
+        [&[FULL_BLOCK][..], &EIGHTHS_UP[1..], &EIGHTHS_RIGHT[1..]].concat()
+    } else {
+        vec![bar_char]
+    };
+    for ch in chars {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: false,
+            role,
+        });
+    }
+}
+
+pub struct Bar {
+    pub label: String,
+    pub value: u64,
+    pub char: char,
+}
+
+/// Length of the bar in eighths of a cell, rounded up so that anything non-zero is visible
+fn eighths(value: u64, max_value: u64, cells: usize) -> usize {
+    if max_value == 0 {
+        0
+    } else {
+        (value.min(max_value) * cells as u64 * 8).div_ceil(max_value) as usize
+    }
+}
+
+/// Cell `idx` of a bar `eighths` long, counting from its base
+fn bar_cell(bar_char: char, eighths: usize, idx: usize, partials: &[char; 8]) -> char {
+    if eighths >= (idx + 1) * 8 {
+        bar_char
+    } else if eighths <= idx * 8 {
+        ' '
+    } else if bar_char == FULL_BLOCK {
+        partials[eighths - idx * 8]
+    } else {
+        bar_char
+    }
+}
+
+/// One labelled bar per line followed by its value, scaled so the longest is `max_width`
+pub fn horizontal_bar_chart(vis: &mut dyn Visualizer, bars: &[Bar], max_width: usize) {
+    let label_width = bars
+        .iter()
+        .map(|b| b.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let max_value = bars.iter().map(|b| b.value).max().unwrap_or(0);
+    for bar in bars {
+        let length = eighths(bar.value, max_value, max_width);
+        let cells: String = (0..length.div_ceil(8))
+            .map(|idx| bar_cell(bar.char, length, idx, &EIGHTHS_RIGHT))
+            .collect();
+        vis.write_line(&format!(
+            "{:>width$} {} {}",
+            bar.label,
+            cells,
+            bar.value,
+            width = label_width
+        ));
+    }
+}
+
+/// Y axis label for the row, only the top, middle and bottom rows are labelled
+fn y_label(row: usize, height: usize, y_max: u64) -> String {
+    if row == height - 1 {
+        y_max.to_string()
+    } else if row == (height - 1) / 2 && height > 2 {
+        (y_max / 2).to_string()
+    } else if row == 0 {
+        "0".to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn x_axis(vis: &mut dyn Visualizer, label_width: usize, columns: usize, x_labels: (&str, &str)) {
+    vis.write_line(&format!(
+        "{} +{}",
+        repeated_char(' ', label_width),
+        repeated_char('-', columns)
+    ));
+    let (first, last) = x_labels;
+    vis.write_line(&format!(
+        "{}  {}{:>width$}",
+        repeated_char(' ', label_width),
+        first,
+        last,
+        // labels are kept apart even when they're wider than the chart
+        width = columns
+            .saturating_sub(first.chars().count())
+            .max(last.chars().count() + 1)
+    ));
+}
+
+/// Column per value with its char, from 0 at the bottom to `y_max` at the top; the x axis is
+/// labelled at both ends
+pub fn vertical_bar_chart(
+    vis: &mut dyn Visualizer,
+    columns: &[(u64, char)],
+    height: usize,
+    y_max: u64,
+    x_labels: (&str, &str),
+) {
+    let label_width = y_max.to_string().len();
+    let lengths: Vec<usize> = columns
+        .iter()
+        .map(|&(value, _)| eighths(value, y_max, height))
+        .collect();
+    for row in (0..height).rev() {
+        vis.write_str(&format!(
+            "{:>width$} |",
+            y_label(row, height, y_max),
+            width = label_width
+        ));
+        for (&(_, bar_char), &length) in columns.iter().zip(lengths.iter()) {
+            vis.write_char(bar_cell(bar_char, length, row, &EIGHTHS_UP));
+        }
+        vis.write_newline();
+    }
+    x_axis(vis, label_width, columns.len(), x_labels);
+}
+
+/// Counts of values falling into `bucket_count` equal parts of `min..max`, as a bar chart.
+/// Nothing is drawn for an empty range or without buckets
+pub fn histogram(
+    vis: &mut dyn Visualizer,
+    values: &[u64],
+    (min, max): (u64, u64),
+    bucket_count: usize,
+    height: usize,
+    bar_char: char,
+) {
+    if max <= min || bucket_count == 0 {
+        return;
+    }
+    let mut counts = vec![0; bucket_count];
+    let span = max - min;
+    for &value in values.iter().filter(|&&v| v >= min && v < max) {
+        counts[((value - min) * bucket_count as u64 / span) as usize] += 1;
+    }
+    let columns: Vec<(u64, char)> = counts.iter().map(|&c| (c, bar_char)).collect();
+    let y_max = *counts.iter().max().unwrap_or(&0);
+    vertical_bar_chart(
+        vis,
+        &columns,
+        height,
+        y_max,
+        (&min.to_string(), &max.saturating_sub(1).to_string()),
+    );
+}
+
+/// Values as one line of block chars, from 0 to the largest of them
+pub fn sparkline(values: &[u64]) -> String {
+    let max_value = values.iter().copied().max().unwrap_or(0).max(1);
+    values
+        .iter()
+        .map(|&v| SPARK_LEVELS[(v * (SPARK_LEVELS.len() - 1) as u64 / max_value) as usize])
+        .collect()
+}
+
+/// Line through values squeezed into at most `width` columns, each showing the last value falling
+/// into it. The x axis spans `x_count` values, of which only the first ones may be known yet,
+/// and `y_max` can be larger than the values, to keep the scale from frame to frame
+pub fn line_plot(
+    vis: &mut dyn Visualizer,
+    values: &[u64],
+    x_count: usize,
+    width: usize,
+    height: usize,
+    y_max: u64,
+    x_labels: (&str, &str),
+) {
+    let columns = x_count.min(width);
+    let label_width = y_max.to_string().len();
+    let column_values: Vec<Option<u64>> = (0..columns)
+        .map(|col| {
+            values
+                .get(((col + 1) * x_count).div_ceil(columns) - 1)
+                .copied()
+        })
+        .collect();
+    for row in (0..height).rev() {
+        vis.write_str(&format!(
+            "{:>width$} |",
+            y_label(row, height, y_max),
+            width = label_width
+        ));
+        for value in column_values.iter() {
+            let is_plotted = value.is_some_and(|value| {
+                (value.min(y_max) * (height - 1) as u64 / y_max.max(1)) as usize == row
+            });
+            vis.write_char(if is_plotted { PLOT_CHAR } else { ' ' });
+        }
+        vis.write_newline();
+    }
+    x_axis(vis, label_width, columns, x_labels);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keeps everything written, frames run together
+    struct StringVisualizer {
+        text: String,
+    }
+
+    impl Visualizer for StringVisualizer {
+        fn write_char(&mut self, ch: char) {
+            self.text.push(ch);
+        }
+
+        fn end_frame(&mut self) {}
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn add_char_visualization_option(&mut self, _opt: CharVisualizationOption) {}
+    }
+
+    fn render<F: FnOnce(&mut dyn Visualizer)>(draw: F) -> String {
+        let mut vis = StringVisualizer {
+            text: String::new(),
+        };
+        draw(&mut vis);
+        vis.text
+    }
+
+    #[test]
+    fn eighths_round_up_and_cap() {
+        assert_eq!(eighths(5, 0, 10), 0);
+        assert_eq!(eighths(0, 1000, 10), 0);
+        assert_eq!(eighths(1, 1000, 10), 1);
+        assert_eq!(eighths(500, 1000, 10), 40);
+        assert_eq!(eighths(2000, 1000, 10), 80);
+    }
+
+    #[test]
+    fn bar_cells_end_in_partial_blocks() {
+        assert_eq!(bar_cell(FULL_BLOCK, 12, 0, &EIGHTHS_UP), FULL_BLOCK);
+        assert_eq!(bar_cell(FULL_BLOCK, 12, 1, &EIGHTHS_UP), '▄');
+        assert_eq!(bar_cell(FULL_BLOCK, 12, 2, &EIGHTHS_UP), ' ');
+        assert_eq!(bar_cell(FULL_BLOCK, 15, 1, &EIGHTHS_RIGHT), '▉');
+        assert_eq!(bar_cell(FULL_BLOCK, 16, 1, &EIGHTHS_RIGHT), FULL_BLOCK);
+        // other chars can't be split, the last cell is drawn whole
+        assert_eq!(bar_cell(LIGHT_BLOCK, 9, 1, &EIGHTHS_UP), LIGHT_BLOCK);
+    }
+
+    #[test]
+    fn vertical_bars_have_eighth_precision() {
+        let columns = [
+            (0, FULL_BLOCK),
+            (1, FULL_BLOCK),
+            (4, FULL_BLOCK),
+            (8, FULL_BLOCK),
+            (9, FULL_BLOCK),
+            (1, SHADED_BLOCK),
+        ];
+        assert_eq!(
+            render(|vis| vertical_bar_chart(vis, &columns, 1, 8, ("a", "f"))),
+            "8 | ▁▄██▓\n  +------\n   a    f\n"
+        );
+    }
+
+    #[test]
+    fn horizontal_bars_have_eighth_precision() {
+        let bar = |label: &str, value| Bar {
+            label: label.to_string(),
+            value,
+            char: FULL_BLOCK,
+        };
+        assert_eq!(
+            render(|vis| horizontal_bar_chart(vis, &[bar("a", 3), bar("bb", 8), bar("c", 1)], 2)),
+            " a ▊ 3\nbb ██ 8\n c ▎ 1\n"
+        );
+    }
+
+    #[test]
+    fn histogram_of_nothing_draws_nothing() {
+        assert_eq!(
+            render(|vis| histogram(vis, &[1, 2], (5, 5), 4, 3, FULL_BLOCK)),
+            ""
+        );
+        assert_eq!(
+            render(|vis| histogram(vis, &[1, 2], (6, 5), 4, 3, FULL_BLOCK)),
+            ""
+        );
+        assert_eq!(
+            render(|vis| histogram(vis, &[1, 2], (0, 5), 0, 3, FULL_BLOCK)),
+            ""
+        );
+    }
+
+    #[test]
+    fn histogram_of_a_single_value() {
+        assert_eq!(
+            render(|vis| histogram(vis, &[3], (0, 4), 4, 2, FULL_BLOCK)),
+            "1 |   █\n0 |   █\n  +----\n   0  3\n"
+        );
+        // values out of the range are left out
+        assert_eq!(
+            render(|vis| histogram(vis, &[4, 9], (0, 4), 2, 1, FULL_BLOCK)),
+            "0 |  \n  +--\n   0 3\n"
+        );
+    }
+
+    #[test]
+    fn sparkline_levels() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0, 0]), "▁▁▁");
+        assert_eq!(sparkline(&[0, 1, 7, 14]), "▁▁▄█");
+        assert_eq!(sparkline(&[5, 5]), "██");
+    }
+
+    #[test]
+    fn line_plot_samples_the_last_value_of_each_column() {
+        let values: Vec<u64> = (0..10).collect();
+        assert_eq!(
+            render(|vis| line_plot(vis, &values, 10, 5, 2, 9, ("0", "9"))),
+            "9 |    *\n0 |**** \n  +-----\n   0   9\n"
+        );
+        // only the known values are plotted
+        assert_eq!(
+            render(|vis| line_plot(vis, &[2, 9], 4, 4, 2, 9, ("0", "3"))),
+            "9 | *  \n0 |*   \n  +----\n   0  3\n"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use colors_transform::{Color, Hsl, Rgb as TransformRgb};
+use image::Rgb;
+
+use super::get_rgb_pixel;
+
+/// Color space in which gradient stops are blended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Rgb,
+    /// Hue goes the shorter way around the color wheel
+    Hsl,
+    /// CIE L*a*b*, perceptually uniform steps
+    Lab,
+}
+
+/// Piecewise gradient through evenly spaced color stops
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    stops: Vec<Rgb<u8>>,
+    interpolation: Interpolation,
+}
+
+impl Gradient {
+    pub fn new(stops: &[Rgb<u8>], interpolation: Interpolation) -> Gradient {
+        assert!(stops.len() >= 2, "Gradient needs at least 2 color stops");
+        Gradient {
+            stops: stops.to_vec(),
+            interpolation,
+        }
+    }
+
+    /// Color for value in 0..1, values outside are clamped
+    pub fn at(&self, t: f32) -> Rgb<u8> {
+        sample_stops(&self.stops, self.interpolation, t)
+    }
+}
+
+fn sample_stops(stops: &[Rgb<u8>], interpolation: Interpolation, t: f32) -> Rgb<u8> {
+    let t = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let idx = (t.floor() as usize).min(stops.len() - 2);
+    let (from, to) = (stops[idx], stops[idx + 1]);
+    let t = t - idx as f32;
+    match interpolation {
+        Interpolation::Rgb => lerp_rgb(from, to, t),
+        Interpolation::Hsl => lerp_hsl(from, to, t),
+        Interpolation::Lab => lerp_lab(from, to, t),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// Dark blue to yellow, perceptually uniform and color blind safe
+    Viridis,
+    /// Black to light yellow through purple and orange
+    Magma,
+    /// Blue through light grey to red, for values with a meaningful middle
+    Diverging,
+}
+
+// sampled at 0, 1/8, ..., 1 from the matplotlib tables, the rest is interpolated
+const VIRIDIS_STOPS: [Rgb<u8>; 9] = [
+    Rgb([68, 1, 84]),
+    Rgb([71, 45, 123]),
+    Rgb([59, 82, 139]),
+    Rgb([44, 114, 142]),
+    Rgb([33, 145, 140]),
+    Rgb([40, 174, 128]),
+    Rgb([94, 201, 98]),
+    Rgb([173, 220, 48]),
+    Rgb([253, 231, 37]),
+];
+
+const MAGMA_STOPS: [Rgb<u8>; 9] = [
+    Rgb([0, 0, 4]),
+    Rgb([28, 16, 68]),
+    Rgb([79, 18, 123]),
+    Rgb([129, 37, 129]),
+    Rgb([181, 54, 122]),
+    Rgb([229, 89, 100]),
+    Rgb([251, 135, 97]),
+    Rgb([254, 194, 135]),
+    Rgb([252, 253, 191]),
+];
+
+// endpoints of Moreland's cool-warm map
+const DIVERGING_STOPS: [Rgb<u8>; 3] = [Rgb([59, 76, 192]), Rgb([221, 221, 221]), Rgb([180, 4, 38])];
+
+impl Colormap {
+    fn stops(&self) -> (&'static [Rgb<u8>], Interpolation) {
+        match self {
+            Colormap::Viridis => (&VIRIDIS_STOPS, Interpolation::Rgb),
+            Colormap::Magma => (&MAGMA_STOPS, Interpolation::Rgb),
+            Colormap::Diverging => (&DIVERGING_STOPS, Interpolation::Lab),
+        }
+    }
+
+    /// Color for value in 0..1
+    pub fn at(&self, t: f32) -> Rgb<u8> {
+        let (stops, interpolation) = self.stops();
+        sample_stops(stops, interpolation, t)
+    }
+
+    /// Color for value in 0..1 using only the `from..to` part of the map, e.g. to skip
+    /// the black end of magma on a black background
+    pub fn at_within(&self, t: f32, from: f32, to: f32) -> Rgb<u8> {
+        self.at(from + t.clamp(0.0, 1.0) * (to - from))
+    }
+}
+
+/// `n` colors with evenly spaced hues, for telling apart categories
+pub fn palette(n: usize, saturation: u8, lightness: u8) -> Vec<Rgb<u8>> {
+    (0..n)
+        .map(|i| get_rgb_pixel((360 * i / n) as u16, saturation, lightness))
+        .collect()
+}
+
+/// Nearest color of the xterm 256 color palette: 6x6x6 cube or 24 step grey ramp
+pub fn to_ansi256(color: Rgb<u8>) -> u8 {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let cube_idx = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap()
+    };
+    let (r, g, b) = (cube_idx(color[0]), cube_idx(color[1]), cube_idx(color[2]));
+    let cube_color = Rgb([CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]]);
+
+    let avg = (color[0] as u32 + color[1] as u32 + color[2] as u32) / 3;
+    let grey_idx = ((avg as i32 - 8) / 10).clamp(0, 23) as u8;
+    let grey_level = 8 + 10 * grey_idx;
+    let grey_color = Rgb([grey_level, grey_level, grey_level]);
+
+    let distance = |other: Rgb<u8>| -> i32 {
+        (0..3)
+            .map(|i| (color[i] as i32 - other[i] as i32).pow(2))
+            .sum()
+    };
+    if distance(grey_color) < distance(cube_color) {
+        232 + grey_idx
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+/// Terminal color, falling back to the 256 color palette on terminals without truecolor
+pub fn to_ansi_colour(color: Rgb<u8>, is_truecolor: bool) -> ansi_term::Colour {
+    if is_truecolor {
+        ansi_term::Colour::RGB(color[0], color[1], color[2])
+    } else {
+        ansi_term::Colour::Fixed(to_ansi256(color))
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn lerp_rgb(from: Rgb<u8>, to: Rgb<u8>, t: f32) -> Rgb<u8> {
+    let channel = |i: usize| lerp(from[i] as f32, to[i] as f32, t).round() as u8;
+    Rgb([channel(0), channel(1), channel(2)])
+}
+
+fn lerp_hsl(from: Rgb<u8>, to: Rgb<u8>, t: f32) -> Rgb<u8> {
+    let to_hsl = |c: Rgb<u8>| TransformRgb::from(c[0] as f32, c[1] as f32, c[2] as f32).to_hsl();
+    let (from, to) = (to_hsl(from), to_hsl(to));
+    let mut hue_delta = to.get_hue() - from.get_hue();
+    if hue_delta > 180.0 {
+        hue_delta -= 360.0;
+    } else if hue_delta < -180.0 {
+        hue_delta += 360.0;
+    }
+    let hue = (from.get_hue() + hue_delta * t).rem_euclid(360.0);
+    let (r, g, b) = Hsl::from(
+        hue,
+        lerp(from.get_saturation(), to.get_saturation(), t),
+        lerp(from.get_lightness(), to.get_lightness(), t),
+    )
+    .to_rgb()
+    .as_tuple();
+    Rgb([r.round() as u8, g.round() as u8, b.round() as u8])
+}
+
+fn lerp_lab(from: Rgb<u8>, to: Rgb<u8>, t: f32) -> Rgb<u8> {
+    let (from, to) = (rgb_to_lab(from), rgb_to_lab(to));
+    lab_to_rgb([
+        lerp(from[0], to[0], t),
+        lerp(from[1], to[1], t),
+        lerp(from[2], to[2], t),
+    ])
+}
+
+// D65 white point
+const WHITE_XYZ: [f32; 3] = [0.95047, 1.0, 1.08883];
+
+fn rgb_to_lab(color: Rgb<u8>) -> [f32; 3] {
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(color[0]), linear(color[1]), linear(color[2]));
+    let xyz = [
+        0.4124 * r + 0.3576 * g + 0.1805 * b,
+        0.2126 * r + 0.7152 * g + 0.0722 * b,
+        0.0193 * r + 0.1192 * g + 0.9505 * b,
+    ];
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (
+        f(xyz[0] / WHITE_XYZ[0]),
+        f(xyz[1] / WHITE_XYZ[1]),
+        f(xyz[2] / WHITE_XYZ[2]),
+    );
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_to_rgb(lab: [f32; 3]) -> Rgb<u8> {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = fy + lab[1] / 500.0;
+    let fz = fy - lab[2] / 200.0;
+    let f_inv = |t: f32| {
+        if t.powi(3) > 0.008856 {
+            t.powi(3)
+        } else {
+            (t - 16.0 / 116.0) / 7.787
+        }
+    };
+    let (x, y, z) = (
+        f_inv(fx) * WHITE_XYZ[0],
+        f_inv(fy) * WHITE_XYZ[1],
+        f_inv(fz) * WHITE_XYZ[2],
+    );
+    let gamma = |c: f32| {
+        let c = if c <= 0.0031308 {
+            12.92 * c
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+    Rgb([
+        gamma(3.2406 * x - 1.5372 * y - 0.4986 * z),
+        gamma(-0.9689 * x + 1.8758 * y + 0.0415 * z),
+        gamma(0.0557 * x - 0.2040 * y + 1.0570 * z),
+    ])
+}
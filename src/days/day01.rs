@@ -1,4 +1,48 @@
-use crate::{solution::Solution, visualizer::Visualizer};
+use crate::{
+    charts::{set_bar_color, vertical_bar_chart, FULL_BLOCK, LIGHT_BLOCK},
+    solution::Solution,
+    theme::Role,
+    visualizer::Visualizer,
+};
+
+const CHART_HEIGHT: usize = 20;
+const BAR_CHAR: char = LIGHT_BLOCK;
+const TOP_BAR_CHAR: char = FULL_BLOCK;
+
+fn setup_visualizer(vis: &mut dyn Visualizer) {
+    set_bar_color(vis, TOP_BAR_CHAR, Role::Highlight);
+}
+
+/// Vertical bar per elf, sorted from the most calories, with the first `top_count` highlighted
+fn visualize_calories(vis: &mut dyn Visualizer, calories: &[u32], top_count: usize) {
+    if !vis.is_enabled() {
+        return;
+    }
+    let mut sorted = calories.to_vec();
+    sorted.sort_by(|a, b| b.cmp(a));
+    let columns: Vec<(u64, char)> = sorted
+        .iter()
+        .enumerate()
+        .map(|(idx, &elf_calories)| {
+            let bar_char = if idx < top_count {
+                TOP_BAR_CHAR
+            } else {
+                BAR_CHAR
+            };
+            (elf_calories as u64, bar_char)
+        })
+        .collect();
+    vertical_bar_chart(
+        vis,
+        &columns,
+        CHART_HEIGHT,
+        sorted[0] as u64,
+        ("most", "least"),
+    );
+    vis.set_stat("elves", &sorted.len());
+    vis.set_stat("top_calories", &sorted.iter().take(top_count).sum::<u32>());
+    vis.end_frame();
+}
 
 pub struct CalorieCouting;
 
@@ -13,15 +57,17 @@ impl Solution for CalorieCouting {
             .collect()
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer);
+        visualizer.set_title("Elf carrying the most calories");
+        visualize_calories(visualizer, &input, 1);
         *(input.iter().max().unwrap())
     }
 
-    fn solve_pt2(
-        &self,
-        mut input: Self::InputT,
-        _visualizer: &mut dyn Visualizer,
-    ) -> Self::OutputT {
+    fn solve_pt2(&self, mut input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer);
+        visualizer.set_title("Three elves carrying the most calories");
+        visualize_calories(visualizer, &input, 3);
         input.sort();
         input.reverse();
         input[0..3].iter().sum()
@@ -1,4 +1,6 @@
+use crate::charts::{line_plot, set_bar_color, PLOT_CHAR};
 use crate::solution::Solution;
+use crate::theme::Role;
 use crate::visualizer::Visualizer;
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -53,18 +55,24 @@ impl Shape {
     }
 }
 
-fn calculate_round_score_pt1(line: &str) -> u32 {
-    let mut chars_iter = line.split(" ");
-
+fn parse_opponent_shape(s: &str) -> Shape {
     use Shape::*;
-    let opponent_shape = match chars_iter.next().unwrap() {
+    match s {
         "A" => Rock,
         "B" => Paper,
         "C" => Scissors,
         _ => {
             panic!("opponent move must be A, B or C");
         }
-    };
+    }
+}
+
+/// Opponent's and my shape, with the second column being my shape
+fn parse_round_pt1(line: &str) -> (Shape, Shape) {
+    let mut chars_iter = line.split(' ');
+
+    use Shape::*;
+    let opponent_shape = parse_opponent_shape(chars_iter.next().unwrap());
     let my_shape = match chars_iter.next().unwrap() {
         "X" => Rock,
         "Y" => Paper,
@@ -73,20 +81,14 @@ fn calculate_round_score_pt1(line: &str) -> u32 {
             panic!("my move must be X, Y or Z");
         }
     };
-    return my_shape.score() + my_shape.match_with(&opponent_shape) as u32;
+    (opponent_shape, my_shape)
 }
-fn calculate_round_score_pt2(line: &str) -> u32 {
-    let mut chars_iter = line.split(" ");
 
-    use Shape::*;
-    let opponent_shape = match chars_iter.next().unwrap() {
-        "A" => Rock,
-        "B" => Paper,
-        "C" => Scissors,
-        _ => {
-            panic!("opponent move must be A, B or C");
-        }
-    };
+/// Opponent's and my shape, with the second column being the outcome I need
+fn parse_round_pt2(line: &str) -> (Shape, Shape) {
+    let mut chars_iter = line.split(' ');
+
+    let opponent_shape = parse_opponent_shape(chars_iter.next().unwrap());
     let my_shape = match chars_iter.next().unwrap() {
         "X" => opponent_shape.get_losing_shape(),
         "Y" => opponent_shape,
@@ -95,7 +97,73 @@ fn calculate_round_score_pt2(line: &str) -> u32 {
             panic!("my move must be X, Y or Z");
         }
     };
-    return my_shape.score() + my_shape.match_with(&opponent_shape) as u32;
+    (opponent_shape, my_shape)
+}
+
+fn round_score(opponent_shape: &Shape, my_shape: &Shape) -> u32 {
+    my_shape.score() + my_shape.match_with(opponent_shape) as u32
+}
+
+const PLOT_WIDTH: usize = 60;
+const PLOT_HEIGHT: usize = 15;
+
+fn setup_visualizer(vis: &mut dyn Visualizer, title: &str) {
+    vis.set_title(title);
+    set_bar_color(vis, PLOT_CHAR, Role::Highlight);
+}
+
+/// Running score after each of the rounds played so far; both axes are scaled to the whole
+/// tournament so the line grows from frame to frame
+fn visualize_running_score(
+    vis: &mut dyn Visualizer,
+    running_scores: &[u64],
+    round_count: usize,
+    max_score: u64,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    line_plot(
+        vis,
+        running_scores,
+        round_count,
+        PLOT_WIDTH,
+        PLOT_HEIGHT,
+        max_score,
+        ("1", &round_count.to_string()),
+    );
+    vis.set_stat(
+        "round",
+        &format!("{}/{}", running_scores.len(), round_count),
+    );
+    vis.set_stat("score", running_scores.last().unwrap_or(&0));
+    vis.end_frame();
+}
+
+fn play(rounds: &[(Shape, Shape)], vis: &mut dyn Visualizer) -> u32 {
+    let running_scores: Vec<u64> = rounds
+        .iter()
+        .scan(0, |score, (opponent_shape, my_shape)| {
+            *score += round_score(opponent_shape, my_shape) as u64;
+            Some(*score)
+        })
+        .collect();
+    let score = running_scores.last().copied().unwrap_or(0);
+
+    // scores are all known upfront, so that the axis fits the final one from the first frame
+    for (round_idx, (opponent_shape, my_shape)) in rounds.iter().enumerate() {
+        if vis.is_enabled() {
+            vis.log(&format!(
+                "round {}: {:?} vs {:?}, +{}",
+                round_idx + 1,
+                my_shape,
+                opponent_shape,
+                round_score(opponent_shape, my_shape)
+            ));
+        }
+        visualize_running_score(vis, &running_scores[..=round_idx], rounds.len(), score);
+    }
+    score as u32
 }
 
 pub struct RockPaperScissors;
@@ -108,17 +176,15 @@ impl Solution for RockPaperScissors {
         input_raw
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        input
-            .split("\n")
-            .map(calculate_round_score_pt1)
-            .sum::<u32>()
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Running score, second column is my shape");
+        let rounds: Vec<(Shape, Shape)> = input.split('\n').map(parse_round_pt1).collect();
+        play(&rounds, visualizer)
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        input
-            .split("\n")
-            .map(calculate_round_score_pt2)
-            .sum::<u32>()
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Running score, second column is the outcome");
+        let rounds: Vec<(Shape, Shape)> = input.split('\n').map(parse_round_pt2).collect();
+        play(&rounds, visualizer)
     }
 }
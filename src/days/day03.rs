@@ -1,5 +1,6 @@
 use crate::solution::Solution;
-use crate::visualizer::Visualizer;
+use crate::theme::Role;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 use itertools::Itertools;
 use std::collections::{hash_map::RandomState, HashSet};
 
@@ -27,6 +28,51 @@ fn find_badge(group_rucksacks: &Vec<String>) -> char {
         .to_owned()
 }
 
+const SHARED_ITEM_MARK: char = '^';
+
+fn setup_visualizer(vis: &mut dyn Visualizer, title: &str) {
+    vis.set_title(title);
+    vis.add_char_visualization_option(CharVisualizationOption {
+        char: SHARED_ITEM_MARK,
+        is_bold: true,
+        role: Role::Highlight,
+    });
+}
+
+/// Item lists one under another, each followed by a line marking where the shared item is
+fn visualize_shared_item(
+    vis: &mut dyn Visualizer,
+    item_lists: &[&str],
+    shared_item: char,
+    priority_sum: u32,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    for items in item_lists {
+        vis.write_line(items);
+        vis.write_line(
+            &items
+                .chars()
+                .map(|item| {
+                    if item == shared_item {
+                        SHARED_ITEM_MARK
+                    } else {
+                        ' '
+                    }
+                })
+                .collect::<String>(),
+        );
+    }
+    vis.log(&format!(
+        "shared item {}, priority {}",
+        shared_item,
+        item_priority(shared_item)
+    ));
+    vis.set_stat("priority_sum", &priority_sum);
+    vis.end_frame();
+}
+
 fn item_priority(item: char) -> u32 {
     let offset = if item.is_ascii_lowercase() {
         96
@@ -48,21 +94,28 @@ impl Solution for RucksacksReorganization {
         input_raw.lines().map(|s| s.to_owned()).collect()
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        input
-            .into_iter()
-            .map(find_misplaced_item)
-            .map(item_priority)
-            .sum()
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Item in both compartments");
+        let mut priority_sum = 0;
+        for rucksack in input.into_iter() {
+            let (first, second) = rucksack.split_at(rucksack.len() / 2);
+            let item = find_misplaced_item(rucksack.clone());
+            priority_sum += item_priority(item);
+            visualize_shared_item(visualizer, &[first, second], item, priority_sum);
+        }
+        priority_sum
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        input
-            .into_iter()
-            .chunks(3)
-            .into_iter()
-            .map(|chunk| find_badge(&(chunk.collect())))
-            .map(item_priority)
-            .sum::<u32>()
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Badge carried by the whole group");
+        let mut priority_sum = 0;
+        for chunk in input.into_iter().chunks(3).into_iter() {
+            let group: Vec<String> = chunk.collect();
+            let badge = find_badge(&group);
+            priority_sum += item_priority(badge);
+            let item_lists: Vec<&str> = group.iter().map(|r| r.as_str()).collect();
+            visualize_shared_item(visualizer, &item_lists, badge, priority_sum);
+        }
+        priority_sum
     }
 }
@@ -1,48 +1,134 @@
 use crate::solution::Solution;
-use crate::visualizer::Visualizer;
+use crate::theme::Role;
+use crate::types::IntervalSet;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 
-pub struct Range(u32, u32);
+/// Sections cleaned by an elf
+pub type Sections = IntervalSet<u32>;
 
-impl Range {
-    fn contains(&self, other: &Range) -> bool {
-        self.0 <= other.0 && self.1 >= other.1
-    }
-
-    fn overlaps(&self, other: &Range) -> bool {
-        !(self.1 < other.0 || self.0 > other.1)
-    }
+fn parse_range(s: &str) -> Sections {
+    let mut it = s.split("-").map(|d| d.parse::<u32>().unwrap());
+    let first = it.next().unwrap();
+    let last = it.next().unwrap();
+    Sections::from(first..last + 1)
 }
 
-fn parse_range(s: &str) -> Range {
-    let mut it = s.split("-").map(|d| d.parse::<u32>().unwrap());
-    Range(it.next().unwrap(), it.next().unwrap())
+/// First and last section, as written in the input
+fn section_bounds(sections: &Sections) -> (u32, u32) {
+    let span = sections.span().unwrap();
+    (span.start, span.end - 1)
 }
 
-fn parse_ranges(line: &str) -> (Range, Range) {
+fn parse_ranges(line: &str) -> (Sections, Sections) {
     let mut it = line.split(",").map(parse_range);
     (it.next().unwrap(), it.next().unwrap())
 }
+
+const PAIRS_PER_FRAME: usize = 10;
+const FIRST_ELF_CHAR: char = '=';
+const SECOND_ELF_CHAR: char = '-';
+const BOTH_ELVES_CHAR: char = '#';
+
+fn setup_visualizer(vis: &mut dyn Visualizer, title: &str) {
+    vis.set_title(title);
+    for (ch, role) in [
+        (FIRST_ELF_CHAR, Role::Series(0)),
+        (SECOND_ELF_CHAR, Role::Series(1)),
+        (BOTH_ELVES_CHAR, Role::Highlight),
+    ] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role,
+        });
+    }
+}
+
+/// Sections of both elves as bars on a shared axis, with the sections they both clean marked;
+/// pairs the puzzle part is counting are labelled
+fn visualize_pairs(
+    vis: &mut dyn Visualizer,
+    pairs: &[(Sections, Sections)],
+    max_section: u32,
+    is_counted: &dyn Fn(&Sections, &Sections) -> bool,
+    count: u32,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    for (r1, r2) in pairs {
+        let bar = |sections: &Sections, own_char: char, other: &Sections| -> String {
+            (1..=max_section)
+                .map(
+                    |section| match (sections.contains(section), other.contains(section)) {
+                        (true, true) => BOTH_ELVES_CHAR,
+                        (true, false) => own_char,
+                        _ => ' ',
+                    },
+                )
+                .collect()
+        };
+        let (r1_first, r1_last) = section_bounds(r1);
+        let (r2_first, r2_last) = section_bounds(r2);
+        vis.write_line(&format!(
+            "{:>2}-{:<2} |{}|",
+            r1_first,
+            r1_last,
+            bar(r1, FIRST_ELF_CHAR, r2)
+        ));
+        vis.write_line(&format!(
+            "{:>2}-{:<2} |{}| {}",
+            r2_first,
+            r2_last,
+            bar(r2, SECOND_ELF_CHAR, r1),
+            if is_counted(r1, r2) { "counted" } else { "" }
+        ));
+        vis.write_newline();
+    }
+    vis.set_stat("count", &count);
+    vis.end_frame();
+}
+
+fn count_pairs(
+    vis: &mut dyn Visualizer,
+    pairs: &[(Sections, Sections)],
+    is_counted: &dyn Fn(&Sections, &Sections) -> bool,
+) -> u32 {
+    let max_section = pairs
+        .iter()
+        .map(|(r1, r2)| section_bounds(r1).1.max(section_bounds(r2).1))
+        .max()
+        .unwrap_or(0);
+    let mut count = 0;
+    for frame_pairs in pairs.chunks(PAIRS_PER_FRAME) {
+        count += frame_pairs
+            .iter()
+            .filter(|(r1, r2)| is_counted(r1, r2))
+            .count() as u32;
+        visualize_pairs(vis, frame_pairs, max_section, is_counted, count);
+    }
+    count
+}
+
 pub struct CampCleanup;
 
 impl Solution for CampCleanup {
-    type InputT = Vec<(Range, Range)>;
+    type InputT = Vec<(Sections, Sections)>;
     type OutputT = u32;
 
     fn parse_input(&self, input_raw: String) -> Self::InputT {
         input_raw.lines().map(parse_ranges).collect()
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        input
-            .iter()
-            .map(|(r1, r2)| (r1.contains(&r2) || r2.contains(&r1)) as u32)
-            .sum::<u32>()
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Pairs where one range fully contains the other");
+        count_pairs(visualizer, &input, &|r1, r2| {
+            r1.is_superset(r2) || r2.is_superset(r1)
+        })
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        input
-            .iter()
-            .map(|(r1, r2)| (r1.overlaps(&r2)) as u32)
-            .sum::<u32>()
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Pairs with overlapping ranges");
+        count_pairs(visualizer, &input, &|r1, r2| r1.intersects(r2))
     }
 }
@@ -1,7 +1,8 @@
-use crate::visualizer::Visualizer;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 use regex::Regex;
 
 use crate::solution::Solution;
+use crate::theme::Role;
 
 type Stacks = Vec<Vec<char>>;
 
@@ -75,6 +76,79 @@ fn concat_top_items(stacks: &Stacks) -> String {
     return res;
 }
 
+fn setup_visualizer(vis: &mut dyn Visualizer, crane_model: &str) {
+    vis.set_title(&format!("CrateMover {}", crane_model));
+    for ch in ['{', '}'] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role: Role::Highlight,
+        });
+    }
+}
+
+/// Draws stacks as columns of [X] crates, top `highlighted_count` crates of the
+/// `highlighted_stack` are drawn as {X}. Frames are padded to `max_height` rows so that
+/// they all have the same size.
+fn visualize_stacks(
+    vis: &mut dyn Visualizer,
+    stacks: &Stacks,
+    max_height: usize,
+    highlighted_stack: usize,
+    highlighted_count: usize,
+) {
+    for level in (0..max_height).rev() {
+        for (stack_idx, stack) in stacks.iter().enumerate() {
+            if stack_idx > 0 {
+                vis.write_char(' ');
+            }
+            match stack.get(level) {
+                Some(crate_)
+                    if stack_idx == highlighted_stack
+                        && level + highlighted_count >= stack.len() =>
+                {
+                    vis.write_str(&format!("{{{}}}", crate_))
+                }
+                Some(crate_) => vis.write_str(&format!("[{}]", crate_)),
+                None => vis.write_str("   "),
+            }
+        }
+        vis.write_newline();
+    }
+    for stack_idx in 0..stacks.len() {
+        if stack_idx > 0 {
+            vis.write_char(' ');
+        }
+        vis.write_str(&format!(" {} ", stack_idx + 1));
+    }
+    vis.write_newline();
+    vis.end_frame();
+}
+
+/// Two frames per move: crates about to be picked up, then the same crates where they landed
+fn visualize_move(
+    vis: &mut dyn Visualizer,
+    stacks_before: &Stacks,
+    stacks_after: &Stacks,
+    md: &MoveDef,
+    move_idx: usize,
+    moves_total: usize,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    let max_height = stacks_before.iter().map(|s| s.len()).sum();
+    vis.set_stat("move", &format!("{}/{}", move_idx + 1, moves_total));
+    vis.log(&format!(
+        "move {} from {} to {}",
+        md.move_count,
+        md.from + 1,
+        md.to + 1
+    ));
+    visualize_stacks(vis, stacks_before, max_height, md.from, md.move_count);
+    visualize_stacks(vis, stacks_after, max_height, md.to, md.move_count);
+}
+
 pub struct SupplyStack;
 
 impl Solution for SupplyStack {
@@ -94,20 +168,34 @@ impl Solution for SupplyStack {
         (parse_stacks(&stacks_init_block), move_defs)
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
         let (mut stacks, move_defs) = input;
-        for md in move_defs.iter() {
+        setup_visualizer(visualizer, "9000");
+        for (move_idx, md) in move_defs.iter().enumerate() {
+            let stacks_before = visualizer.is_enabled().then(|| stacks.clone());
             for _ in 0..md.move_count {
                 let crate_ = stacks[md.from].pop().unwrap();
                 stacks[md.to].push(crate_);
             }
+            if let Some(stacks_before) = stacks_before {
+                visualize_move(
+                    visualizer,
+                    &stacks_before,
+                    &stacks,
+                    md,
+                    move_idx,
+                    move_defs.len(),
+                );
+            }
         }
         concat_top_items(&stacks)
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
         let (mut stacks, move_defs) = input;
-        for md in move_defs.iter() {
+        setup_visualizer(visualizer, "9001");
+        for (move_idx, md) in move_defs.iter().enumerate() {
+            let stacks_before = visualizer.is_enabled().then(|| stacks.clone());
             let mut picked_up: Vec<char> = Vec::new();
             for _ in 0..md.move_count {
                 picked_up.push(stacks[md.from].pop().unwrap());
@@ -116,6 +204,16 @@ impl Solution for SupplyStack {
             for c in picked_up {
                 stacks[md.to].push(c);
             }
+            if let Some(stacks_before) = stacks_before {
+                visualize_move(
+                    visualizer,
+                    &stacks_before,
+                    &stacks,
+                    md,
+                    move_idx,
+                    move_defs.len(),
+                );
+            }
         }
         concat_top_items(&stacks)
     }
@@ -1,5 +1,5 @@
-use crate::visualizer::Visualizer;
-use std::str::Chars;
+use crate::theme::Role;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 
 use crate::solution::Solution;
 
@@ -58,10 +58,25 @@ impl Buffer {
         return true;
     }
 
-    fn find_distinct(&mut self, characters: Chars) -> Option<u32> {
-        for character in characters {
+    /// Values currently in the buffer, oldest first
+    fn window(&self) -> Vec<EncodedChar> {
+        if self.is_filled {
+            [
+                &self.buf[self.write_idx..self.size],
+                &self.buf[..self.write_idx],
+            ]
+            .concat()
+        } else {
+            self.buf[..self.write_idx].to_vec()
+        }
+    }
+
+    fn find_distinct(&mut self, datastream: &str, vis: &mut dyn Visualizer) -> Option<u32> {
+        for character in datastream.chars() {
             self.update(encode_char(&character));
-            if self.all_distinct() {
+            let is_marker = self.all_distinct();
+            visualize_buffer(vis, datastream, self, is_marker);
+            if is_marker {
                 return Some(self.seen_values);
             }
         }
@@ -69,6 +84,100 @@ impl Buffer {
     }
 }
 
+const CONTEXT_BEFORE: usize = 40;
+const CONTEXT_AFTER: usize = 20;
+const ALPHABET_SIZE: usize = 26;
+
+const WINDOW_MARK: char = '^';
+const DUPLICATE_MARK: char = '!';
+const SET_BIT: char = '#';
+const UNSET_BIT: char = '.';
+
+fn setup_visualizer(vis: &mut dyn Visualizer, title: &str) {
+    vis.set_title(title);
+    for (ch, role) in [
+        (WINDOW_MARK, Role::Frontier),
+        (DUPLICATE_MARK, Role::Highlight),
+        (SET_BIT, Role::Frontier),
+    ] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role,
+        });
+    }
+}
+
+/// Bits of the value with bit 0 (letter a) on the left, bits that were already set in `acc`
+/// are the duplicates
+fn format_bits(value: EncodedChar, acc: EncodedChar) -> String {
+    (0..ALPHABET_SIZE)
+        .map(|bit| match (value >> bit & 1, acc >> bit & 1) {
+            (1, 1) => DUPLICATE_MARK,
+            (1, _) => SET_BIT,
+            _ => UNSET_BIT,
+        })
+        .collect()
+}
+
+/// Stream around the window with the window and its duplicates marked below it, then the
+/// encoded window values OR-ed together the way `all_distinct` does it
+fn visualize_buffer(vis: &mut dyn Visualizer, datastream: &str, buffer: &Buffer, is_marker: bool) {
+    if !vis.is_enabled() {
+        return;
+    }
+    let window = buffer.window();
+    let window_end = buffer.seen_values as usize;
+    let window_start = window_end - window.len();
+    let shown_from = window_start.saturating_sub(CONTEXT_BEFORE);
+    let shown_to = (window_end + CONTEXT_AFTER).min(datastream.len());
+
+    let mut acc: EncodedChar = 0;
+    let mut value_lines: Vec<String> = Vec::new();
+    let mut marks = String::new();
+    for (&value, ch) in window
+        .iter()
+        .zip(datastream[window_start..window_end].chars())
+    {
+        let is_duplicate = value & acc > 0;
+        value_lines.push(format!("{}  {}", ch, format_bits(value, acc)));
+        marks.push(if is_duplicate {
+            DUPLICATE_MARK
+        } else {
+            WINDOW_MARK
+        });
+        acc |= value;
+    }
+
+    vis.write_line(&datastream[shown_from..shown_to]);
+    vis.write_line(&format!(
+        "{}{}",
+        " ".repeat(window_start - shown_from),
+        marks
+    ));
+    vis.write_newline();
+    vis.write_line(&format!(
+        "   {}",
+        (0..ALPHABET_SIZE)
+            .map(|i| (b'a' + i as u8) as char)
+            .collect::<String>()
+    ));
+    for line in value_lines {
+        vis.write_line(&line);
+    }
+    vis.write_line(&format!("|  {}", format_bits(acc, 0)));
+
+    vis.set_stat("processed", &buffer.seen_values);
+    vis.set_stat("all_distinct", &is_marker);
+    if is_marker {
+        vis.log(&format!(
+            "marker found after {} characters",
+            buffer.seen_values
+        ));
+    }
+    vis.end_frame();
+}
+
 pub struct TuningTrouble;
 
 impl Solution for TuningTrouble {
@@ -76,14 +185,16 @@ impl Solution for TuningTrouble {
     type OutputT = u32;
 
     fn parse_input(&self, input_raw: String) -> Self::InputT {
-        input_raw
+        input_raw.trim_end().to_string()
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        Buffer::new(4).find_distinct(input.chars()).unwrap()
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Looking for the start-of-packet marker");
+        Buffer::new(4).find_distinct(&input, visualizer).unwrap()
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        Buffer::new(14).find_distinct(input.chars()).unwrap()
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer, "Looking for the start-of-message marker");
+        Buffer::new(14).find_distinct(&input, visualizer).unwrap()
     }
 }
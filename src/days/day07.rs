@@ -1,7 +1,8 @@
-use crate::visualizer::Visualizer;
-use std::collections::HashMap;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 
+use crate::graph_export::{Graph, GraphExport};
 use crate::solution::Solution;
+use crate::theme::Role;
 
 #[derive(Debug)]
 enum TerminalCommand<'b> {
@@ -10,94 +11,237 @@ enum TerminalCommand<'b> {
 }
 
 impl TerminalCommand<'_> {
-    fn parse(line: &str) -> Option<TerminalCommand> {
-        let mut words_iter = line.split(" ");
+    fn parse(line: &str) -> Option<TerminalCommand<'_>> {
+        let mut words_iter = line.split(' ');
         if words_iter.next().unwrap() != "$" {
             return None;
         }
         match words_iter.next().unwrap() {
-            "ls" => return Some(TerminalCommand::LS),
-            "cd" => return Some(TerminalCommand::CD(words_iter.next().unwrap())),
+            "ls" => Some(TerminalCommand::LS),
+            "cd" => Some(TerminalCommand::CD(words_iter.next().unwrap())),
             _ => panic!("Unknown command"),
         }
     }
 }
 
-fn parse_dir_sizes(terminal_output: &str) -> HashMap<Vec<&str>, u32> {
-    let mut result: HashMap<Vec<&str>, u32> = HashMap::new();
-    let mut current_dir: Vec<&str> = Vec::new();
-    let mut current_dir_size: u32 = 0;
-
-    for terminal_line in terminal_output.lines() {
-        match TerminalCommand::parse(terminal_line) {
-            Some(TerminalCommand::CD(dir)) => {
-                let mut partial_dir: Vec<&str> = Vec::new();
-                for current_dir_part in current_dir.iter() {
-                    partial_dir.push(current_dir_part);
-                    if !result.contains_key(&partial_dir) {
-                        result.insert(partial_dir.clone(), 0);
-                    }
-                    *result.get_mut(&partial_dir).unwrap() += current_dir_size
-                }
-                current_dir_size = 0;
-                if dir == ".." {
+#[derive(Debug)]
+pub struct Directory {
+    name: String,
+    files: Vec<(String, u32)>,
+    subdirs: Vec<Directory>,
+}
+
+impl Directory {
+    fn new(name: &str) -> Directory {
+        Directory {
+            name: name.to_string(),
+            files: Vec::new(),
+            subdirs: Vec::new(),
+        }
+    }
+
+    fn parse(terminal_output: &str) -> Directory {
+        let mut root = Directory::new("/");
+        // path from the root
+        let mut current_dir: Vec<&str> = Vec::new();
+
+        for terminal_line in terminal_output.lines() {
+            match TerminalCommand::parse(terminal_line) {
+                Some(TerminalCommand::CD("/")) => current_dir.clear(),
+                Some(TerminalCommand::CD("..")) => {
                     current_dir.pop();
-                } else {
-                    current_dir.push(dir);
                 }
-            }
-            Some(TerminalCommand::LS) => {}
-            None => {
-                let mut words = terminal_line.split(" ");
-                let maybe_file_size = words.next().unwrap();
-                if maybe_file_size != "dir" {
-                    let file_size = maybe_file_size
-                        .parse::<u32>()
-                        .expect("File size must be a number");
-                    current_dir_size += file_size;
+                Some(TerminalCommand::CD(dir)) => current_dir.push(dir),
+                Some(TerminalCommand::LS) => {}
+                None => {
+                    let (size_or_dir, name) = terminal_line
+                        .split_once(' ')
+                        .expect("ls output must be size (or dir) and name");
+                    let dir = root.subdir_mut(&current_dir);
+                    if size_or_dir == "dir" {
+                        dir.subdir_mut(&[name]);
+                    } else {
+                        let file_size = size_or_dir
+                            .parse::<u32>()
+                            .expect("File size must be a number");
+                        dir.files.push((name.to_string(), file_size));
+                    }
                 }
             }
         }
+        root
+    }
+
+    /// Directory at the relative path, missing directories along the way are created
+    fn subdir_mut(&mut self, path: &[&str]) -> &mut Directory {
+        match path.split_first() {
+            None => self,
+            Some((name, rest)) => {
+                let idx = match self.subdirs.iter().position(|d| d.name == *name) {
+                    Some(idx) => idx,
+                    None => {
+                        self.subdirs.push(Directory::new(name));
+                        self.subdirs.len() - 1
+                    }
+                };
+                self.subdirs[idx].subdir_mut(rest)
+            }
+        }
+    }
+
+    fn total_size(&self) -> u32 {
+        self.files.iter().map(|(_, size)| size).sum::<u32>()
+            + self.subdirs.iter().map(|d| d.total_size()).sum::<u32>()
+    }
+
+    /// This directory and all directories below it, in preorder
+    fn dirs(&self) -> Vec<&Directory> {
+        let mut dirs = vec![self];
+        for subdir in self.subdirs.iter() {
+            dirs.extend(subdir.dirs());
+        }
+        dirs
+    }
+
+    /// Total sizes of this directory and all directories below it
+    fn dir_sizes(&self) -> Vec<u32> {
+        self.dirs().iter().map(|d| d.total_size()).collect()
     }
 
-    // code repeated but whatever
-    let mut partial_dir: Vec<&str> = Vec::new();
-    for current_dir_part in current_dir.iter() {
-        partial_dir.push(current_dir_part);
-        if !result.contains_key(&partial_dir) {
-            result.insert(partial_dir.clone(), 0);
+    /// Adds nodes for this directory and everything below it with edges from parents to
+    /// children, ids are numbered in preorder starting from `next_id`; returns this node's id
+    fn add_to_graph(&self, graph: &mut Graph, next_id: &mut usize) -> String {
+        let id = format!("n{}", next_id);
+        *next_id += 1;
+        let size = self.total_size().to_string();
+        graph
+            .add_node(
+                &id,
+                &[&format!("{}/", self.name.trim_end_matches('/')), &size],
+            )
+            .color = Some("blue".to_string());
+        for (file_name, file_size) in self.files.iter() {
+            let file_id = format!("n{}", next_id);
+            *next_id += 1;
+            graph.add_node(&file_id, &[file_name, &file_size.to_string()]);
+            graph.add_edge(&id, &file_id);
+        }
+        for subdir in self.subdirs.iter() {
+            let subdir_id = subdir.add_to_graph(graph, next_id);
+            graph.add_edge(&id, &subdir_id);
         }
-        *result.get_mut(&partial_dir).unwrap() += current_dir_size
+        id
+    }
+}
+
+fn setup_visualizer(vis: &mut dyn Visualizer) {
+    for ch in [SMALL_DIR_MARK, CHOSEN_DIR_MARK] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role: Role::Highlight,
+        });
+    }
+    vis.add_char_visualization_option(CharVisualizationOption {
+        char: BIG_ENOUGH_DIR_MARK,
+        is_bold: true,
+        role: Role::Frontier,
+    });
+}
+
+const SMALL_DIR_MARK: char = '*';
+const BIG_ENOUGH_DIR_MARK: char = '+';
+const CHOSEN_DIR_MARK: char = '<';
+
+type DirMark<'a> = &'a dyn Fn(&Directory, u32) -> Option<char>;
+
+fn write_dir_line(vis: &mut dyn Visualizer, line_start: &str, dir: &Directory, mark: DirMark) {
+    let size = dir.total_size();
+    vis.write_str(&format!("{} {}", line_start, size));
+    if let Some(mark_char) = mark(dir, size) {
+        vis.write_str(&format!(" {}", mark_char));
+    }
+    vis.write_newline();
+}
+
+fn visualize_subdirs(vis: &mut dyn Visualizer, dir: &Directory, prefix: &str, mark: DirMark) {
+    for (idx, subdir) in dir.subdirs.iter().enumerate() {
+        let is_last = idx == dir.subdirs.len() - 1;
+        let branch = if is_last { "└── " } else { "├── " };
+        write_dir_line(
+            vis,
+            &format!("{}{}{}/", prefix, branch, subdir.name),
+            subdir,
+            mark,
+        );
+        let subdir_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
+        visualize_subdirs(vis, subdir, &subdir_prefix, mark);
+    }
+}
+
+/// Directories only with their total sizes, like `du`, marked by the `mark` callback
+fn visualize_tree(vis: &mut dyn Visualizer, root: &Directory, mark: DirMark) {
+    if !vis.is_enabled() {
+        return;
     }
-    result
+    write_dir_line(vis, "/", root, mark);
+    visualize_subdirs(vis, root, "", mark);
+    vis.end_frame();
 }
 
 pub struct NoSpaceLeftOnDevice;
 
 impl Solution for NoSpaceLeftOnDevice {
-    type InputT = String;
+    type InputT = Directory;
     type OutputT = u32;
 
     fn parse_input(&self, input_raw: String) -> Self::InputT {
-        input_raw
+        Directory::parse(&input_raw)
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        parse_dir_sizes(&input)
-            .values()
-            .filter(|v| **v < 100_000)
-            .sum()
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        let res = input.dir_sizes().iter().filter(|v| **v < 100_000).sum();
+
+        setup_visualizer(visualizer);
+        visualizer.set_title("Directories under 100000");
+        visualizer.set_stat("sum", &res);
+        visualize_tree(visualizer, &input, &|_, size| {
+            (size < 100_000).then_some(SMALL_DIR_MARK)
+        });
+        res
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        let dir_sizes = parse_dir_sizes(&input);
-        let current_fs_size = dir_sizes[&vec!["/"]];
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        let current_fs_size = input.total_size();
         let current_free_space = 70_000_000 - current_fs_size;
         let space_to_clean = 30_000_000 - current_free_space;
-        *dir_sizes
-            .values()
-            .filter(|v| **v > space_to_clean)
-            .min()
-            .unwrap()
+        let chosen_dir = input
+            .dirs()
+            .into_iter()
+            .filter(|d| d.total_size() > space_to_clean)
+            .min_by_key(|d| d.total_size())
+            .unwrap();
+        let res = chosen_dir.total_size();
+
+        setup_visualizer(visualizer);
+        visualizer.set_title("Smallest directory to delete");
+        visualizer.set_stat("space_to_clean", &space_to_clean);
+        visualizer.set_stat("deleted", &res);
+        visualize_tree(visualizer, &input, &|dir, size| {
+            if std::ptr::eq(dir, chosen_dir) {
+                Some(CHOSEN_DIR_MARK)
+            } else {
+                (size > space_to_clean).then_some(BIG_ENOUGH_DIR_MARK)
+            }
+        });
+        res
+    }
+}
+
+impl GraphExport for NoSpaceLeftOnDevice {
+    fn to_graph(&self, input: &Self::InputT) -> Graph {
+        let mut graph = Graph::new("filesystem", true);
+        input.add_to_graph(&mut graph, &mut 0);
+        graph
     }
 }
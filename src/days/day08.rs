@@ -1,31 +1,26 @@
-use crate::visualizer::Visualizer;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 use std::fmt::Display;
 
 use crate::solution::Solution;
-#[allow(unused_imports)]
-use crate::utils::{print_2d_vec, read_input};
+use crate::theme::Role;
+use crate::types::{Coords, Grid};
 
 #[derive(Debug)]
 pub struct Forest {
-    tree_heights: Vec<Vec<u8>>,
-    width: usize,
-    height: usize,
+    tree_heights: Grid<u8>,
 }
 
-type VisibilityMap = Vec<Vec<bool>>;
+type VisibilityMap = Grid<bool>;
 
 #[allow(dead_code)]
 fn print_visibility_mask(vm: &VisibilityMap) {
-    for row in vm {
-        for item in row {
-            print!("{}", if *item { "x" } else { "." });
-        }
-        println!("");
-    }
-    println!("");
+    println!(
+        "{}",
+        vm.map(|is_visible| if *is_visible { 'x' } else { '.' })
+    );
 }
 
-type ScenicScoreMap = Vec<Vec<u32>>;
+type ScenicScoreMap = Grid<u32>;
 
 #[derive(PartialEq, Eq, Debug)]
 enum Direction {
@@ -36,105 +31,94 @@ enum Direction {
 }
 
 impl Direction {
-    fn is_vertical(&self) -> bool {
-        return *self == Direction::TB || *self == Direction::BT;
+    fn name(&self) -> &'static str {
+        match self {
+            Direction::TB => "top",
+            Direction::BT => "bottom",
+            Direction::LR => "left",
+            Direction::RL => "right",
+        }
+    }
+
+    fn arrow(&self) -> char {
+        match self {
+            Direction::TB => 'v',
+            Direction::BT => '^',
+            Direction::LR => '>',
+            Direction::RL => '<',
+        }
+    }
+
+    /// Step of the line of sight when looking from the edge in this direction
+    fn step(&self) -> Coords<i64> {
+        match self {
+            Direction::TB => Coords { x: 0, y: 1 },
+            Direction::BT => Coords { x: 0, y: -1 },
+            Direction::LR => Coords { x: 1, y: 0 },
+            Direction::RL => Coords { x: -1, y: 0 },
+        }
     }
 }
 
+// scenic score heatmap chars, from lowest to highest
+const SCENIC_SCORE_RAMP: [char; 10] = ['.', ',', ':', ';', '=', '+', '*', '#', '%', '@'];
+const BEST_TREE_CHAR: char = 'X';
+
 impl Forest {
     fn parse(input: &str) -> Forest {
-        let mut th: Vec<Vec<u8>> = Vec::new();
-        for (i, line) in input.lines().enumerate() {
-            th.push(Vec::new());
-            for character in line.chars() {
-                th[i].push(character.to_string().parse().unwrap())
-            }
-        }
-        let width = th.len();
-        let height = th[0].len();
         Forest {
-            tree_heights: th,
-            width: width,
-            height: height,
+            tree_heights: Grid::parse(input, |_, ch| {
+                ch.to_digit(10).expect("Tree height must be a digit") as u8
+            }),
         }
     }
 
+    /// How many trees are between the tree and the forest edge in the direction
+    fn depth(&self, direction: &Direction, c: Coords<usize>) -> usize {
+        match direction {
+            Direction::TB => c.y,
+            Direction::BT => self.tree_heights.height() - 1 - c.y,
+            Direction::LR => c.x,
+            Direction::RL => self.tree_heights.width() - 1 - c.x,
+        }
+    }
+
+    /// Trees on the edge the direction looks from
+    fn edge(&self, direction: &Direction) -> Vec<Coords<usize>> {
+        self.tree_heights
+            .coords()
+            .filter(|c| self.depth(direction, *c) == 0)
+            .collect()
+    }
+
     fn empty_visibility_map(&self) -> VisibilityMap {
-        vec![vec![false; self.width]; self.height]
+        Grid::new(self.tree_heights.width(), self.tree_heights.height(), false)
     }
 
     fn visibility_map(&self, direction: &Direction) -> VisibilityMap {
         let mut visibility_map: VisibilityMap = self.empty_visibility_map();
-
-        let los_range = if direction.is_vertical() {
-            0..self.width
-        } else {
-            0..self.height
-        };
-
-        let depth_range: Vec<usize> = match direction {
-            Direction::TB => (0..self.height).collect(),
-            Direction::BT => (0..self.height).rev().collect(),
-            Direction::LR => (0..self.width).collect(),
-            Direction::RL => ((0..self.width).rev()).collect(),
-        };
-
-        for los_idx in los_range {
-            let mut max_height = 0;
-            for (depth_iter_idx, depth_idx) in depth_range.iter().enumerate() {
-                let (i, j) = if direction.is_vertical() {
-                    (*depth_idx, los_idx)
-                } else {
-                    (los_idx, *depth_idx)
-                };
-                if depth_iter_idx == 0 || self.tree_heights[i][j] > max_height {
-                    visibility_map[i][j] = true;
-                    max_height = self.tree_heights[i][j];
+        for edge_tree in self.edge(direction) {
+            visibility_map[edge_tree] = true;
+            let mut max_height = self.tree_heights[edge_tree];
+            for c in self.tree_heights.line_of_sight(edge_tree, direction.step()) {
+                if self.tree_heights[c] > max_height {
+                    visibility_map[c] = true;
+                    max_height = self.tree_heights[c];
                 }
             }
         }
         visibility_map
     }
 
+    /// How many trees can be seen from each tree looking the same way as the direction,
+    /// up to and including the first one that's at least as tall
     fn scenic_score_map(&self, direction: &Direction) -> ScenicScoreMap {
-        let mut ss_map = vec![vec![0; self.width]; self.height];
-
-        let los_range = if direction.is_vertical() {
-            0..self.width
-        } else {
-            0..self.height
-        };
-
-        let depth_range: Vec<usize> = match direction {
-            Direction::TB => (0..self.height).collect(),
-            Direction::BT => (0..self.height).rev().collect(),
-            Direction::LR => (0..self.width).collect(),
-            Direction::RL => ((0..self.width).rev()).collect(),
-        };
-
-        for los_idx in los_range {
-            for depth_range_idx in 0..(depth_range.len() - 1) {
-                for lookahead_depth_range_idx in (depth_range_idx + 1)..(depth_range.len()) {
-                    let (i, j, i_lookahead, j_lookahead) = if direction.is_vertical() {
-                        (
-                            depth_range[depth_range_idx],
-                            los_idx,
-                            depth_range[lookahead_depth_range_idx],
-                            los_idx,
-                        )
-                    } else {
-                        (
-                            los_idx,
-                            depth_range[depth_range_idx],
-                            los_idx,
-                            depth_range[lookahead_depth_range_idx],
-                        )
-                    };
-                    ss_map[i][j] += 1;
-                    // println!("look at {}, {} from {}, {}", i_lookahead, j_lookahead, i, j);
-                    if self.tree_heights[i_lookahead][j_lookahead] >= self.tree_heights[i][j] {
-                        break;
-                    }
+        let mut ss_map = Grid::new(self.tree_heights.width(), self.tree_heights.height(), 0);
+        for (c, height) in self.tree_heights.iter() {
+            for c_lookahead in self.tree_heights.line_of_sight(c, direction.step()) {
+                ss_map[c] += 1;
+                if self.tree_heights[c_lookahead] >= *height {
+                    break;
                 }
             }
         }
@@ -144,16 +128,109 @@ impl Forest {
 
 impl Display for Forest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.tree_heights.iter() {
-            for tree in row {
-                write!(f, "{}", tree)?;
+        write!(f, "{}", self.tree_heights)
+    }
+}
+
+fn setup_visualizer(vis: &mut dyn Visualizer) {
+    for height in 0..=9u8 {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: (b'0' + height) as char,
+            is_bold: false,
+            role: Role::Heat(height as f32 / 9.0),
+        });
+    }
+    for direction in [Direction::TB, Direction::LR, Direction::BT, Direction::RL] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: direction.arrow(),
+            is_bold: true,
+            role: Role::Frontier,
+        });
+    }
+    for (idx, ch) in SCENIC_SCORE_RAMP.iter().enumerate() {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: *ch,
+            is_bold: false,
+            role: Role::Heat(idx as f32 / (SCENIC_SCORE_RAMP.len() - 1) as f32),
+        });
+    }
+    vis.add_char_visualization_option(CharVisualizationOption {
+        char: BEST_TREE_CHAR,
+        is_bold: true,
+        role: Role::Highlight,
+    });
+}
+
+/// One frame per line of trees swept from the edge: trees are revealed as they become
+/// visible, the sweep line is drawn with arrows where trees are hidden
+fn visualize_visibility_sweep(
+    vis: &mut dyn Visualizer,
+    forest: &Forest,
+    revealed: &VisibilityMap,
+    directional_map: &VisibilityMap,
+    direction: &Direction,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    vis.set_title(&format!("Looking from the {}", direction.name()));
+    let max_depth = match direction {
+        Direction::TB | Direction::BT => forest.tree_heights.height(),
+        Direction::LR | Direction::RL => forest.tree_heights.width(),
+    };
+    for swept_depth in 0..max_depth {
+        let mut visible_count = 0;
+        for (c, tree_height) in forest.tree_heights.iter() {
+            let depth = forest.depth(direction, c);
+            let is_visible = revealed[c] || (directional_map[c] && depth <= swept_depth);
+            if is_visible {
+                visible_count += 1;
+                vis.write_char((b'0' + tree_height) as char);
+            } else if depth == swept_depth {
+                vis.write_char(direction.arrow());
+            } else {
+                vis.write_char(' ');
+            }
+            if c.x == forest.tree_heights.width() - 1 {
+                vis.write_newline();
             }
-            write!(f, "\n")?;
         }
-        return Result::Ok(());
+        vis.set_stat("visible", &visible_count);
+        vis.end_frame();
     }
 }
 
+/// Scenic scores span orders of magnitude, so the heatmap is log-scaled
+fn visualize_scenic_scores(
+    vis: &mut dyn Visualizer,
+    scenic_score_map: &ScenicScoreMap,
+    best_tree: Option<Coords<usize>>,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    let max_score = scenic_score_map.iter().map(|(_, s)| *s).max().unwrap_or(0);
+    let log_max = (1.0 + max_score as f32).ln();
+    for (c, score) in scenic_score_map.iter() {
+        if best_tree == Some(c) {
+            vis.write_char(BEST_TREE_CHAR);
+        } else {
+            let heat = if log_max > 0.0 {
+                (1.0 + *score as f32).ln() / log_max
+            } else {
+                0.0
+            };
+            let ramp_idx = (heat * (SCENIC_SCORE_RAMP.len() - 1) as f32).round() as usize;
+            vis.write_char(SCENIC_SCORE_RAMP[ramp_idx]);
+        }
+        if c.x == scenic_score_map.width() - 1 {
+            vis.write_newline();
+        }
+    }
+    vis.set_stat("max_score", &max_score);
+    vis.end_frame();
+}
+
 pub struct TreetopTreeHouse;
 
 impl Solution for TreetopTreeHouse {
@@ -164,37 +241,59 @@ impl Solution for TreetopTreeHouse {
         Forest::parse(&input_raw)
     }
 
-    fn solve_pt1(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer);
         let mut visibility_map = input.empty_visibility_map();
         for direction in [Direction::TB, Direction::LR, Direction::BT, Direction::RL] {
             // println!("{:?}", direction);
             let directional_map = input.visibility_map(&direction);
             // print_visibility_mask(&directional_map);
-            for i in 0..input.width {
-                for j in 0..input.height {
-                    visibility_map[i][j] |= directional_map[i][j];
-                }
+            visualize_visibility_sweep(
+                visualizer,
+                &input,
+                &visibility_map,
+                &directional_map,
+                &direction,
+            );
+            for (c, is_visible) in directional_map.iter() {
+                visibility_map[c] |= *is_visible;
             }
         }
         visibility_map
             .iter()
-            .flatten()
-            .map(|is_visible| *is_visible as u32)
+            .map(|(_, is_visible)| *is_visible as u32)
             .sum::<u32>()
     }
 
-    fn solve_pt2(&self, input: Self::InputT, _visualizer: &mut dyn Visualizer) -> Self::OutputT {
-        let mut scenic_score_map = vec![vec![1; input.width]; input.height];
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer);
+        let mut scenic_score_map = input.tree_heights.map(|_| 1);
+        let mut looked_from: Vec<&str> = Vec::new();
         for direction in [Direction::TB, Direction::LR, Direction::BT, Direction::RL] {
             // println!("{:?}", direction);
             let directional_map = input.scenic_score_map(&direction);
             // print_2d_vec(&directional_map);
-            for i in 0..input.width {
-                for j in 0..input.height {
-                    scenic_score_map[i][j] *= directional_map[i][j];
-                }
+            for (c, score) in directional_map.iter() {
+                scenic_score_map[c] *= *score;
             }
+            looked_from.push(direction.name());
+            visualizer.set_title(&format!(
+                "Scenic score looking from the {}",
+                looked_from.join(", ")
+            ));
+            visualize_scenic_scores(visualizer, &scenic_score_map, None);
         }
-        *scenic_score_map.iter().flatten().max().unwrap()
+
+        let (best_tree, best_score) = scenic_score_map
+            .iter()
+            .max_by_key(|(_, score)| **score)
+            .unwrap();
+        visualizer.set_title("Best tree for the tree house");
+        visualizer.set_stat(
+            "best_tree",
+            &format!("row {}, column {}", best_tree.y, best_tree.x),
+        );
+        visualize_scenic_scores(visualizer, &scenic_score_map, Some(best_tree));
+        *best_score
     }
 }
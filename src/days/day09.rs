@@ -1,12 +1,16 @@
+use std::cmp::{max, min};
 use std::str::FromStr;
-use std::{cmp::max, collections::HashSet};
 
 use itertools::Itertools;
 use strum_macros::{Display, EnumString};
 
-use crate::color;
+use crate::theme::Role;
+use crate::viewport::Viewport;
 use crate::visualizer::CharVisualizationOption;
-use crate::{solution::Solution, types::Coords};
+use crate::{
+    solution::Solution,
+    types::{Coords, InfiniteGrid},
+};
 
 #[derive(Debug, EnumString, Display)]
 pub enum Direction {
@@ -17,7 +21,7 @@ pub enum Direction {
 }
 
 impl Direction {
-    fn delta(&self) -> Coords<i32> {
+    fn delta(&self) -> Coords<i64> {
         match self {
             Direction::L => Coords { x: -1, y: 0 },
             Direction::R => Coords { x: 1, y: 0 },
@@ -49,46 +53,49 @@ impl Solution for RopeBridge {
         input: Self::InputT,
         visualizer: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
-        let (mut vis_hh, mut vis_hw) = (10, 10);
-        let mut head = Coords::<i32> { x: 0, y: 0 };
-        let mut tail = Coords::<i32> { x: 0, y: 0 };
-        let mut tail_positions: HashSet<Coords<i32>> = HashSet::new();
+        let mut viewport = Viewport::new(41, 21).y_up();
+        let mut head = Coords::<i64> { x: 0, y: 0 };
+        let mut tail = Coords::<i64> { x: 0, y: 0 };
+        let mut tail_positions = InfiniteGrid::new(false);
 
         for (direction, amount) in input {
             for _ in 0..amount {
                 head += direction.delta();
-                let mut delta = head - tail;
-                if delta.x.abs() > 1 || delta.y.abs() > 1 {
-                    delta.x = num::clamp(delta.x, -1, 1);
-                    delta.y = num::clamp(delta.y, -1, 1);
-                    tail += delta;
+                if head.chebyshev_distance(&tail) > 1 {
+                    tail += (head - tail).signum();
                 }
-                tail_positions.insert(tail.clone());
+                tail_positions.set(tail, true);
 
                 if visualizer.is_enabled() {
-                    vis_hw = max(max(head.x.abs(), tail.x.abs()), vis_hw);
-                    vis_hh = max(max(head.y.abs(), tail.y.abs()), vis_hh);
-                    for y in (-vis_hh..=vis_hh).rev() {
-                        for x in -vis_hw..=vis_hw {
-                            let c = Coords { x, y };
-                            visualizer.write_char(if c == head {
-                                'H'
-                            } else if c == tail {
-                                'T'
-                            } else if c.is_origin() {
-                                's'
-                            } else {
-                                '.'
-                            })
-                        }
-                        visualizer.write_newline();
-                    }
+                    // the whole rope path is always in view, downsampled when it gets too large
+                    let (visited_min, visited_max) = tail_positions.bounding_box().unwrap();
+                    viewport.fit(
+                        Coords {
+                            x: min(visited_min.x, head.x),
+                            y: min(visited_min.y, head.y),
+                        },
+                        Coords {
+                            x: max(visited_max.x, head.x),
+                            y: max(visited_max.y, head.y),
+                        },
+                    );
+
+                    let mut canvas =
+                        tail_positions.draw(
+                            &viewport,
+                            '.',
+                            |&visited| if visited { '#' } else { '.' },
+                        );
+                    canvas.plot(Coords::origin(), 's');
+                    canvas.plot(tail, 'T');
+                    canvas.plot(head, 'H');
+                    canvas.write_to(visualizer);
                     visualizer.end_frame();
                 }
             }
         }
 
-        tail_positions.len()
+        tail_positions.iter().count()
     }
 
     fn solve_pt2(
@@ -97,89 +104,72 @@ impl Solution for RopeBridge {
         visualizer: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
         const KNOTS: usize = 10;
-        const VIS_HALFSIDE: i32 = 12;
+        const VIS_HALFSIDE: usize = 12;
 
         visualizer.add_char_visualization_option(CharVisualizationOption {
             char: 'H',
             is_bold: true,
-            color: color::get_rgb_pixel(0, 100, 100),
+            role: Role::Agent,
         });
         visualizer.add_char_visualization_option(CharVisualizationOption {
             char: 'T',
             is_bold: true,
-            color: color::get_rgb_pixel(0, 100, 80),
+            role: Role::Highlight,
         });
         let int2char = |i: usize| format!("{}", i).chars().next().unwrap();
         for knot_idx in 1..=9 {
             visualizer.add_char_visualization_option(CharVisualizationOption {
                 char: int2char(knot_idx),
                 is_bold: false,
-                color: color::get_rgb_pixel((270 * (knot_idx - 1) / 8) as u16, 100, 50),
+                role: Role::Series(knot_idx - 1),
             });
         }
 
+        let mut viewport = Viewport::new(2 * VIS_HALFSIDE + 1, 2 * VIS_HALFSIDE + 1).y_up();
         let mut rope = [Coords::origin(); KNOTS];
-        let mut tail_positions: HashSet<Coords<i32>> = HashSet::new();
+        let mut tail_positions = InfiniteGrid::new(false);
 
         for (direction, amount) in input {
             for _ in 0..amount {
                 let mut current_delta = direction.delta();
                 for knot_idx in 0..KNOTS - 1 {
                     rope[knot_idx] += current_delta;
-                    current_delta = rope[knot_idx] - rope[knot_idx + 1];
-                    if current_delta.x.abs() > 1 || current_delta.y.abs() > 1 {
-                        current_delta.x = num::clamp(current_delta.x, -1, 1);
-                        current_delta.y = num::clamp(current_delta.y, -1, 1);
+                    if rope[knot_idx].chebyshev_distance(&rope[knot_idx + 1]) > 1 {
+                        current_delta = (rope[knot_idx] - rope[knot_idx + 1]).signum();
                     } else {
                         current_delta = Coords::origin();
                         break; // if a knot is stationary, the rest of the rope is too
                     }
                 }
                 rope[KNOTS - 1] += current_delta;
-                tail_positions.insert(rope[KNOTS - 1].clone());
+                tail_positions.set(rope[KNOTS - 1], true);
 
                 if visualizer.is_enabled() {
-                    for y in ((rope[KNOTS - 1].y - VIS_HALFSIDE)
-                        ..=(rope[KNOTS - 1].y + VIS_HALFSIDE))
-                        .rev()
-                    {
-                        for x in
-                            (rope[KNOTS - 1].x - VIS_HALFSIDE)..=(rope[KNOTS - 1].x + VIS_HALFSIDE)
-                        {
-                            let c = Coords { x, y };
-                            let knot_idx = rope.iter().find_position(|k| **k == c);
-                            if let Some((idx, _)) = knot_idx {
-                                visualizer.write_char(if idx == 0 {
-                                    'H'
-                                } else if idx == KNOTS - 1 {
-                                    'T'
-                                } else {
-                                    int2char(idx)
-                                })
+                    viewport.follow(rope[0], VIS_HALFSIDE / 2);
+                    viewport.render(visualizer, |c, _| {
+                        if let Some((idx, _)) = rope.iter().find_position(|k| **k == c) {
+                            if idx == 0 {
+                                'H'
+                            } else if idx == KNOTS - 1 {
+                                'T'
                             } else {
-                                const GRID_LINES_EACH: i32 = 10;
-                                if x % GRID_LINES_EACH == 0 {
-                                    if y % GRID_LINES_EACH == 0 {
-                                        visualizer.write_char('┼');
-                                    } else {
-                                        visualizer.write_char('|')
-                                    }
-                                } else {
-                                    if y % GRID_LINES_EACH == 0 {
-                                        visualizer.write_char('-');
-                                    } else {
-                                        visualizer.write_char(' ');
-                                    }
-                                }
+                                int2char(idx)
+                            }
+                        } else {
+                            const GRID_LINES_EACH: i64 = 10;
+                            match (c.x % GRID_LINES_EACH == 0, c.y % GRID_LINES_EACH == 0) {
+                                (true, true) => '┼',
+                                (true, false) => '|',
+                                (false, true) => '-',
+                                (false, false) => ' ',
                             }
                         }
-                        visualizer.write_newline();
-                    }
+                    });
                     visualizer.end_frame();
                 }
             }
         }
 
-        tail_positions.len()
+        tail_positions.iter().count()
     }
 }
@@ -58,11 +58,11 @@ impl Solution for CathodeRayTube {
         let mut cycle: u32 = 1;
         let mut strength: i32 = 0;
 
+        visualizer.set_title("Recording signal strength");
         for (op_idx, op) in input.enumerate() {
-            visualizer.write_line(&format!(
-                "Running op {} {:?} [x = {}, cycle = {}]",
-                op_idx, op, x, cycle
-            ));
+            visualizer.set_stat("x", &x);
+            visualizer.set_stat("cycle", &cycle);
+            visualizer.log(&format!("Running op {} {:?}", op_idx, op));
             let (new_cycle, new_x) = match op {
                 Op::Noop => (cycle + 1, x),
                 Op::Addx(v) => (cycle + 2, x + v),
@@ -74,11 +74,12 @@ impl Solution for CathodeRayTube {
             if let Some(recording_cycle) = maybe_recording_cycle {
                 let strength_add = x * (recording_cycle as i32);
                 strength += strength_add;
-                visualizer.write_line(&format!(
-                    "During this operation we record signal strength {} (now {})!",
-                    strength_add, strength
+                visualizer.log(&format!(
+                    "During this operation we record signal strength {}!",
+                    strength_add
                 ));
             }
+            visualizer.set_stat("strength", &strength);
             x = new_x;
             cycle = new_cycle;
             visualizer.end_frame();
@@ -98,6 +99,7 @@ impl Solution for CathodeRayTube {
         const PIXELS: usize = 40;
         let mut screen = [[false; PIXELS]; LINES];
 
+        visualizer.set_title("CRT screen");
         for (_, op) in input.enumerate() {
             let (cycle_after, x_after) = match op {
                 Op::Noop => (cycle_curr + 1, x_curr),
@@ -109,10 +111,10 @@ impl Solution for CathodeRayTube {
                 if (pixel - x_curr).abs() <= 1 {
                     screen[cycle as usize / PIXELS][pixel as usize] = true;
                 }
-                visualizer.write_line(&format!(
-                    "cycle {}, x = {}, {} {:?}",
-                    cycle,
-                    x_curr,
+                visualizer.set_stat("cycle", &cycle);
+                visualizer.set_stat("x", &x_curr);
+                visualizer.log(&format!(
+                    "{} {:?}",
                     if cycle == cycle_curr {
                         "starting"
                     } else {
@@ -2,7 +2,14 @@ use std::collections::VecDeque;
 
 use regex::Regex;
 
-use crate::{solution::Solution, utils::repeated_char, visualizer::Visualizer};
+use crate::{
+    charts::{
+        histogram, horizontal_bar_chart, set_bar_color, Bar, FULL_BLOCK, LIGHT_BLOCK, SHADED_BLOCK,
+    },
+    solution::Solution,
+    theme::Role,
+    visualizer::Visualizer,
+};
 
 #[derive(Debug)]
 enum Operation {
@@ -46,51 +53,100 @@ pub struct Monkey {
     if_false_throw_to: usize,
 }
 
-fn visualize_monkeys(
+const BAR_MAX_WIDTH: usize = 50;
+const HISTOGRAM_BUCKETS: usize = 48;
+const HISTOGRAM_HEIGHT: usize = 8;
+
+const TOP_BAR_CHAR: char = FULL_BLOCK;
+const BAR_CHAR: char = SHADED_BLOCK;
+const QUEUE_CHAR: char = '=';
+const HISTOGRAM_CHAR: char = LIGHT_BLOCK;
+
+fn setup_visualizer(vis: &mut dyn Visualizer, title: &str) {
+    vis.set_title(title);
+    for (ch, role) in [
+        (TOP_BAR_CHAR, Role::Highlight),
+        (BAR_CHAR, Role::Visited),
+        (QUEUE_CHAR, Role::Frontier),
+        (HISTOGRAM_CHAR, Role::Fill),
+    ] {
+        set_bar_color(vis, ch, role);
+    }
+}
+
+/// Per-round summary: inspections so far (the two most active monkeys, whose product is
+/// the answer, are highlighted), items waiting for each monkey and how worry levels of all
+/// items are spread modulo the magic constant
+fn visualize_round(
     vis: &mut dyn Visualizer,
     round: usize,
-    monkeys: &Vec<Monkey>,
-    inspected: Option<u64>,
-    op: Option<&Operation>,
+    monkeys: &[Monkey],
+    monkey_business: &[u64],
+    magic_constant: u64,
 ) {
     if !vis.is_enabled() {
         return;
     }
-    vis.write_line(&format!("round: {}", round + 1));
-    if let Some(inspected_worry) = inspected {
-        vis.write_str(&format!("ispected: {: >12}", inspected_worry));
-        if let Some(operation) = op {
-            vis.write_line(&format!(" <- {:?}", operation));
-        } else {
-            vis.write_newline();
-        }
-    } else {
-        vis.write_char('\n');
-    }
-    for m in monkeys {
-        vis.write_str(&format!("[{: ^10}]", m.idx));
-    }
-    vis.write_newline();
-    let mut item_idx = 0;
-    loop {
-        let mut has_printed = false;
-        for m in monkeys {
-            if m.items.len() > item_idx {
-                vis.write_str(&format!("{: ^12}", m.items[item_idx]));
-                has_printed = true;
+    vis.set_stat("round", &round);
+    let mut sorted_business = monkey_business.to_vec();
+    sorted_business.sort_by(|a, b| b.cmp(a));
+    let top_threshold = sorted_business.get(1).copied().unwrap_or(0);
+    vis.set_stat(
+        "monkey_business",
+        &sorted_business.iter().take(2).product::<u64>(),
+    );
+
+    vis.write_line("Inspections");
+    // ties for the second place are all highlighted, there's no telling them apart
+    let inspection_bars: Vec<Bar> = monkeys
+        .iter()
+        .zip(monkey_business)
+        .map(|(m, &inspections)| Bar {
+            label: format!("Monkey {}", m.idx),
+            value: inspections,
+            char: if inspections > 0 && inspections >= top_threshold {
+                TOP_BAR_CHAR
             } else {
-                vis.write_str(&repeated_char(' ', 12));
-            }
-        }
-        vis.write_newline();
-        item_idx += 1;
-        if !has_printed {
-            break;
-        }
-    }
+                BAR_CHAR
+            },
+        })
+        .collect();
+    horizontal_bar_chart(vis, &inspection_bars, BAR_MAX_WIDTH);
+    vis.write_newline();
+
+    vis.write_line("Items waiting");
+    let queue_bars: Vec<Bar> = monkeys
+        .iter()
+        .map(|m| Bar {
+            label: format!("Monkey {}", m.idx),
+            value: m.items.len() as u64,
+            char: QUEUE_CHAR,
+        })
+        .collect();
+    horizontal_bar_chart(vis, &queue_bars, BAR_MAX_WIDTH);
+    vis.write_newline();
+
+    vis.write_line(&format!("Worry levels mod {}", magic_constant));
+    let worry_levels: Vec<u64> = monkeys
+        .iter()
+        .flat_map(|m| m.items.iter())
+        .map(|worry_level| worry_level % magic_constant)
+        .collect();
+    histogram(
+        vis,
+        &worry_levels,
+        (0, magic_constant),
+        HISTOGRAM_BUCKETS,
+        HISTOGRAM_HEIGHT,
+        HISTOGRAM_CHAR,
+    );
     vis.end_frame();
 }
 
+fn magic_constant(monkeys: &[Monkey]) -> u64 {
+    monkeys.iter().map(|m| m.test_divisible_by).product()
+}
+
 pub struct MonkeyInTheMiddle;
 
 impl Solution for MonkeyInTheMiddle {
@@ -168,23 +224,16 @@ impl Solution for MonkeyInTheMiddle {
         mut monkeys: Self::InputT,
         vis: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
-        visualize_monkeys(vis, 0, &monkeys, None, None);
-
         let mut monkey_business: Vec<u64> = vec![0; monkeys.len()];
+        let magic_constant = magic_constant(&monkeys);
+        setup_visualizer(vis, "20 rounds, worry divided by 3");
+        visualize_round(vis, 0, &monkeys, &monkey_business, magic_constant);
 
-        for round in 0..20 {
+        for round in 1..=20 {
             for m_id in 0..monkeys.len() {
                 while let Some(worry_level) = monkeys[m_id].items.pop_front() {
                     monkey_business[m_id] += 1;
-                    visualize_monkeys(
-                        vis,
-                        round,
-                        &monkeys,
-                        Some(worry_level),
-                        Some(&monkeys[m_id].operation),
-                    );
                     let worry_level_testing = monkeys[m_id].operation.perform(&worry_level) / 3;
-                    visualize_monkeys(vis, round, &monkeys, Some(worry_level_testing), None);
                     let throw_to = if worry_level_testing % monkeys[m_id].test_divisible_by == 0 {
                         monkeys[m_id].if_true_throw_to
                     } else {
@@ -193,6 +242,7 @@ impl Solution for MonkeyInTheMiddle {
                     monkeys[throw_to].items.push_back(worry_level_testing);
                 }
             }
+            visualize_round(vis, round, &monkeys, &monkey_business, magic_constant);
         }
 
         monkey_business.sort();
@@ -206,24 +256,16 @@ impl Solution for MonkeyInTheMiddle {
         vis: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
         let mut monkey_business: Vec<u64> = vec![0; monkeys.len()];
-        let magic_constant: u64 = monkeys.iter().map(|m| m.test_divisible_by).product();
-        // println!("Magic constant: {}", magic_constant);
+        let magic_constant = magic_constant(&monkeys);
+        setup_visualizer(vis, "10000 rounds, worry kept modulo the magic constant");
+        visualize_round(vis, 0, &monkeys, &monkey_business, magic_constant);
 
-        visualize_monkeys(vis, 0, &monkeys, None, None);
-        for round in 0..10000 {
+        for round in 1..=10000 {
             for m_id in 0..monkeys.len() {
                 while let Some(worry_level) = monkeys[m_id].items.pop_front() {
                     monkey_business[m_id] += 1;
-                    visualize_monkeys(
-                        vis,
-                        round,
-                        &monkeys,
-                        Some(worry_level),
-                        Some(&monkeys[m_id].operation),
-                    );
                     let worry_level_testing =
                         monkeys[m_id].operation.perform(&worry_level) % magic_constant;
-                    visualize_monkeys(vis, round, &monkeys, Some(worry_level_testing), None);
                     let throw_to = if worry_level_testing % monkeys[m_id].test_divisible_by == 0 {
                         monkeys[m_id].if_true_throw_to
                     } else {
@@ -232,6 +274,7 @@ impl Solution for MonkeyInTheMiddle {
                     monkeys[throw_to].items.push_back(worry_level_testing);
                 }
             }
+            visualize_round(vis, round, &monkeys, &monkey_business, magic_constant);
         }
 
         monkey_business.sort();
@@ -1,75 +1,66 @@
 use std::collections::HashSet;
 
 use crate::{
-    color::get_rgb_pixel, solution::Solution, types::Coords, visualizer::CharVisualizationOption,
+    graph_export::{Graph, GraphExport},
+    solution::Solution,
+    theme::Role,
+    types::{Coords, Grid},
+    visualizer::CharVisualizationOption,
     visualizer::Visualizer,
 };
 
 #[derive(Debug)]
 pub struct HeightMap {
-    map: Vec<Vec<u8>>,
-    width: usize,
-    height: usize,
+    map: Grid<u8>,
     start: Coords<usize>,
     end: Coords<usize>,
 }
 
 impl HeightMap {
     fn at(&self, c: &Coords<usize>) -> u8 {
-        self.map[c.y][c.x]
+        self.map[*c]
     }
 
     fn steps_from(&self, c: &Coords<usize>, is_uphill: bool) -> Vec<Coords<usize>> {
-        let mut res: Vec<Coords<usize>> = Vec::new();
-        let current_height = self.map[c.y][c.x];
-
-        let is_step_ok: Box<dyn Fn(u8) -> bool> = if is_uphill {
-            Box::new(|height_step: u8| height_step <= current_height + 1)
-        } else {
-            Box::new(|height_step: u8| height_step >= current_height - 1)
-        };
-
-        let i = c.y as i32;
-        let j = c.x as i32;
-        for (di, dj) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-            let i_step = i + di;
-            let j_step = j + dj;
-            if i_step >= 0
-                && i_step < self.height as i32
-                && j_step >= 0
-                && j_step < self.width as i32
-                && is_step_ok(self.map[i_step as usize][j_step as usize])
-            {
-                res.push(Coords {
-                    y: i_step as usize,
-                    x: j_step as usize,
-                });
-            }
-        }
-        res
+        let current_height = self.at(c);
+        self.map
+            .neighbors4(*c)
+            .filter(|next| {
+                let height_step = self.at(next);
+                if is_uphill {
+                    height_step <= current_height + 1
+                } else {
+                    height_step + 1 >= current_height
+                }
+            })
+            .collect()
     }
 }
 
+fn node_id(c: &Coords<usize>) -> String {
+    format!("r{}c{}", c.y, c.x)
+}
+
 fn setup_visualizer(vis: &mut dyn Visualizer) {
     for h in 0..26 {
         vis.add_char_visualization_option(CharVisualizationOption {
             char: (h as u8 + 97) as char,
             is_bold: false,
-            color: get_rgb_pixel(0, 0, (40.0 + 60.0 * h as f32 / 26.0) as u8),
+            role: Role::Heat(h as f32 / 25.0),
         })
     }
     for ch in ['[', ']'] {
         vis.add_char_visualization_option(CharVisualizationOption {
             char: ch,
             is_bold: true,
-            color: get_rgb_pixel(0, 100, 50),
+            role: Role::Frontier,
         })
     }
     for ch in ['(', ')'] {
         vis.add_char_visualization_option(CharVisualizationOption {
             char: ch,
             is_bold: true,
-            color: get_rgb_pixel(35, 100, 50),
+            role: Role::Visited,
         })
     }
 }
@@ -83,19 +74,18 @@ fn visualize(
     if !vis.is_enabled() {
         return;
     }
-    for i in 0..hm.height {
-        for j in 0..hm.width {
-            let c = Coords { y: i, x: j };
-            let ch = (hm.map[i][j] + 97) as char;
-            if current.contains(&c) {
-                vis.write_str(&format!("[{}]", ch));
-            } else if visited.contains(&c) {
-                vis.write_str(&format!("({})", ch));
-            } else {
-                vis.write_str(&format!(" {} ", ch));
-            }
+    for (c, height) in hm.map.iter() {
+        let ch = (height + 97) as char;
+        if current.contains(&c) {
+            vis.write_str(&format!("[{}]", ch));
+        } else if visited.contains(&c) {
+            vis.write_str(&format!("({})", ch));
+        } else {
+            vis.write_str(&format!(" {} ", ch));
+        }
+        if c.x == hm.map.width() - 1 {
+            vis.write_newline();
         }
-        vis.write_newline();
     }
     vis.end_frame();
 }
@@ -107,33 +97,23 @@ impl Solution for HillClimbingAlgorithm {
     type OutputT = usize;
 
     fn parse_input(&self, input_raw: String) -> Self::InputT {
-        let mut map: Vec<Vec<u8>> = Vec::new();
         let mut start: Option<Coords<usize>> = None;
         let mut end: Option<Coords<usize>> = None;
-        for (i, line) in input_raw.lines().enumerate() {
-            map.push(Vec::new());
-            for (j, ch) in line.chars().enumerate() {
-                map.last_mut().unwrap().push(match ch {
-                    'S' => {
-                        start = Some(Coords { y: i, x: j });
-                        0
-                    }
-                    'E' => {
-                        end = Some(Coords { y: i, x: j });
-                        25
-                    }
-                    _ => ch as u8 - 97,
-                })
+        let map = Grid::parse(&input_raw, |c, ch| match ch {
+            'S' => {
+                start = Some(c);
+                0
             }
-        }
-        let width = map.iter().next().unwrap().len();
-        let height = map.len();
+            'E' => {
+                end = Some(c);
+                25
+            }
+            _ => ch as u8 - 97,
+        });
         HeightMap {
             map,
             start: start.unwrap(),
             end: end.unwrap(),
-            width,
-            height,
         }
     }
 
@@ -143,11 +123,13 @@ impl Solution for HillClimbingAlgorithm {
         vis: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
         setup_visualizer(vis);
+        vis.set_title("Climbing from the start");
         let mut visited: HashSet<Coords<usize>> = HashSet::new();
         let mut current: HashSet<Coords<usize>> = HashSet::from([input.start]);
 
         let mut steps_so_far = 0;
         loop {
+            vis.set_stat("steps_so_far", &steps_so_far);
             visualize(vis, &input, &visited, &current);
             let mut next: HashSet<Coords<usize>> = HashSet::new();
             for c in current.iter() {
@@ -173,11 +155,13 @@ impl Solution for HillClimbingAlgorithm {
         vis: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
         setup_visualizer(vis);
+        vis.set_title("Descending from the end to any lowest point");
         let mut visited: HashSet<Coords<usize>> = HashSet::new();
         let mut current: HashSet<Coords<usize>> = HashSet::from([input.end]);
 
         let mut steps_so_far = 0;
         loop {
+            vis.set_stat("steps_so_far", &steps_so_far);
             visualize(vis, &input, &visited, &current);
             let mut next: HashSet<Coords<usize>> = HashSet::new();
             for c in current.iter() {
@@ -197,3 +181,29 @@ impl Solution for HillClimbingAlgorithm {
         }
     }
 }
+
+impl GraphExport for HillClimbingAlgorithm {
+    /// Edge from every square to each neighbour it can step to (at most one higher)
+    fn to_graph(&self, input: &Self::InputT) -> Graph {
+        let mut graph = Graph::new("heightmap", true);
+        for (c, height) in input.map.iter() {
+            let label = if c == input.start {
+                'S'
+            } else if c == input.end {
+                'E'
+            } else {
+                (height + 97) as char
+            };
+            let node = graph.add_node(&node_id(&c), &[&label.to_string()]);
+            if c == input.start || c == input.end {
+                node.color = Some("red".to_string());
+            }
+        }
+        for c in input.map.coords() {
+            for c_next in input.steps_from(&c, true) {
+                graph.add_edge(&node_id(&c), &node_id(&c_next));
+            }
+        }
+        graph
+    }
+}
@@ -1,8 +1,14 @@
-use std::{cmp::Ordering, fmt::Display, str::Chars};
+use std::{
+    cmp::{max, Ordering},
+    fmt::Display,
+    str::Chars,
+};
 
 use itertools::Itertools;
 
 use crate::solution::Solution;
+use crate::theme::Role;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Value {
@@ -97,50 +103,232 @@ impl Value {
     }
 }
 
-fn is_ordered_correctly(left: &Value, right: &Value) -> Option<bool> {
-    match (left, right) {
-        (Value::Int(int_left), Value::Int(int_right)) => {
-            if int_left == int_right {
-                None
-            } else {
-                Some(int_left < int_right)
-            }
+#[derive(Debug, Clone)]
+enum ComparisonStepKind {
+    Ints(u32, u32),
+    LeftPromoted(u32),
+    RightPromoted(u32),
+    LeftRanOut,
+    RightRanOut,
+    BothRanOut,
+}
+
+/// One comparison made on the way to the verdict, at the `path` of list indices from the
+/// packet roots. Promoted ints are treated as lists, so the path can go one level below them.
+#[derive(Debug, Clone)]
+struct ComparisonStep {
+    path: Vec<usize>,
+    kind: ComparisonStepKind,
+}
+
+impl Display for ComparisonStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ComparisonStepKind::Ints(l, r) if l == r => write!(f, "{} = {}, continue", l, r),
+            ComparisonStepKind::Ints(l, r) if l < r => write!(f, "{} < {}, right order", l, r),
+            ComparisonStepKind::Ints(l, r) => write!(f, "{} > {}, wrong order", l, r),
+            ComparisonStepKind::LeftPromoted(i) => write!(f, "left {} promoted to [{}]", i, i),
+            ComparisonStepKind::RightPromoted(i) => write!(f, "right {} promoted to [{}]", i, i),
+            ComparisonStepKind::LeftRanOut => write!(f, "left ran out of items, right order"),
+            ComparisonStepKind::RightRanOut => write!(f, "right ran out of items, wrong order"),
+            ComparisonStepKind::BothRanOut => write!(f, "both ran out of items, continue"),
         }
-        (Value::List(items_left), Value::List(items_right)) => {
-            let mut iter_left = items_left.iter();
-            let mut iter_right = items_right.iter();
-            loop {
-                let maybe_item_left = iter_left.next();
-                let maybe_item_right = iter_right.next();
-                match (maybe_item_left, maybe_item_right) {
-                    (None, None) => {
-                        return None;
-                    }
-                    (None, Some(_v)) => {
-                        return Some(true);
-                    }
-                    (Some(_v), None) => {
-                        return Some(false);
-                    }
-                    (Some(item_left), Some(item_right)) => {
-                        if let Some(items_ordered_correctly) =
-                            is_ordered_correctly(item_left, item_right)
-                        {
-                            return Some(items_ordered_correctly);
+    }
+}
+
+#[derive(Debug)]
+struct ComparisonTrace {
+    steps: Vec<ComparisonStep>,
+    /// None if packets are equal
+    verdict: Option<bool>,
+}
+
+impl ComparisonTrace {
+    fn deciding_step(&self) -> Option<&ComparisonStep> {
+        self.verdict.and(self.steps.last())
+    }
+}
+
+fn is_ordered_correctly(left: &Value, right: &Value) -> ComparisonTrace {
+    fn compare(
+        left: &Value,
+        right: &Value,
+        path: &mut Vec<usize>,
+        steps: &mut Vec<ComparisonStep>,
+    ) -> Option<bool> {
+        let mut add_step = |kind: ComparisonStepKind| {
+            steps.push(ComparisonStep {
+                path: path.clone(),
+                kind,
+            })
+        };
+        match (left, right) {
+            (Value::Int(int_left), Value::Int(int_right)) => {
+                add_step(ComparisonStepKind::Ints(*int_left, *int_right));
+                if int_left == int_right {
+                    None
+                } else {
+                    Some(int_left < int_right)
+                }
+            }
+            (Value::List(items_left), Value::List(items_right)) => {
+                let mut idx = 0;
+                loop {
+                    path.push(idx);
+                    let res = match (items_left.get(idx), items_right.get(idx)) {
+                        (None, None) => {
+                            steps.push(ComparisonStep {
+                                path: path.clone(),
+                                kind: ComparisonStepKind::BothRanOut,
+                            });
+                            path.pop();
+                            return None;
+                        }
+                        (None, Some(_v)) => {
+                            steps.push(ComparisonStep {
+                                path: path.clone(),
+                                kind: ComparisonStepKind::LeftRanOut,
+                            });
+                            Some(true)
                         }
+                        (Some(_v), None) => {
+                            steps.push(ComparisonStep {
+                                path: path.clone(),
+                                kind: ComparisonStepKind::RightRanOut,
+                            });
+                            Some(false)
+                        }
+                        (Some(item_left), Some(item_right)) => {
+                            compare(item_left, item_right, path, steps)
+                        }
+                    };
+                    path.pop();
+                    if res.is_some() {
+                        return res;
                     }
+                    idx += 1;
                 }
             }
+            (list_value, Value::Int(i)) => {
+                add_step(ComparisonStepKind::RightPromoted(*i));
+                compare(
+                    list_value,
+                    &Value::List(Vec::from([Value::Int(*i)])),
+                    path,
+                    steps,
+                )
+            }
+            (Value::Int(i), list_value) => {
+                add_step(ComparisonStepKind::LeftPromoted(*i));
+                compare(
+                    &Value::List(Vec::from([Value::Int(*i)])),
+                    list_value,
+                    path,
+                    steps,
+                )
+            }
         }
-        (list_value, Value::Int(i)) => {
-            is_ordered_correctly(list_value, &Value::List(Vec::from([Value::Int(*i)])))
-        }
-        (Value::Int(i), list_value) => {
-            is_ordered_correctly(&Value::List(Vec::from([Value::Int(*i)])), list_value)
+    }
+
+    let mut steps: Vec<ComparisonStep> = Vec::new();
+    let verdict = compare(left, right, &mut Vec::new(), &mut steps);
+    ComparisonTrace { steps, verdict }
+}
+
+// marks around the element compared at the current step and the one deciding the order
+const CURRENT_MARKS: (char, char) = ('{', '}');
+const DECIDING_MARKS: (char, char) = ('<', '>');
+
+/// Formats the value with the element at `path` wrapped in `marks`. Path going below an int
+/// (promoted to list) marks the int, path one past the end of a list marks the missing item.
+fn format_marked(value: &Value, path: &[usize], marks: (char, char)) -> String {
+    let (open, close) = marks;
+    match (value, path.split_first()) {
+        (_, None) | (Value::Int(_), Some(_)) => format!("{}{}{}", open, value, close),
+        (Value::List(items), Some((marked_idx, rest))) => {
+            let mut res = String::from("[");
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    res.push_str(", ");
+                }
+                if idx == *marked_idx {
+                    res.push_str(&format_marked(item, rest, marks));
+                } else {
+                    res.push_str(&item.to_string());
+                }
+            }
+            if *marked_idx >= items.len() {
+                if !items.is_empty() {
+                    res.push_str(", ");
+                }
+                res.push(open);
+                res.push(close);
+            }
+            res.push(']');
+            res
         }
     }
 }
 
+fn setup_visualizer(vis: &mut dyn Visualizer) {
+    for ch in [CURRENT_MARKS.0, CURRENT_MARKS.1] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role: Role::Frontier,
+        });
+    }
+    for ch in [DECIDING_MARKS.0, DECIDING_MARKS.1] {
+        vis.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role: Role::Highlight,
+        });
+    }
+}
+
+fn visualize_step(
+    vis: &mut dyn Visualizer,
+    left: &Value,
+    right: &Value,
+    step: &ComparisonStep,
+    marks: (char, char),
+) {
+    let left_str = format_marked(left, &step.path, marks);
+    let right_str = format_marked(right, &step.path, marks);
+    let width = max(left_str.chars().count(), "left".len());
+    vis.write_line(&format!("{:<width$}   right", "left", width = width));
+    vis.write_line(&format!(
+        "{:<width$}   {}",
+        left_str,
+        right_str,
+        width = width
+    ));
+    vis.log(&step.to_string());
+    vis.end_frame();
+}
+
+/// Frame for every step of the comparison, the last one with the deciding element marked
+fn visualize_comparison(
+    vis: &mut dyn Visualizer,
+    left: &Value,
+    right: &Value,
+    trace: &ComparisonTrace,
+) {
+    if !vis.is_enabled() {
+        return;
+    }
+    for (step_idx, step) in trace.steps.iter().enumerate() {
+        let is_deciding = step_idx == trace.steps.len() - 1 && trace.verdict.is_some();
+        let marks = if is_deciding {
+            DECIDING_MARKS
+        } else {
+            CURRENT_MARKS
+        };
+        visualize_step(vis, left, right, step, marks);
+    }
+}
+
 pub struct DistressSignal;
 
 impl Solution for DistressSignal {
@@ -161,29 +349,23 @@ impl Solution for DistressSignal {
         res
     }
 
-    fn solve_pt1(
-        &self,
-        input: Self::InputT,
-        _visualizer: &mut dyn crate::visualizer::Visualizer,
-    ) -> Self::OutputT {
-        input
-            .iter()
-            .enumerate()
-            .map(|(idx, (l, r))| {
-                if is_ordered_correctly(l, r).unwrap() {
-                    (idx + 1) as usize
-                } else {
-                    0
-                }
-            })
-            .sum()
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
+        setup_visualizer(visualizer);
+        let mut res = 0;
+        for (idx, (l, r)) in input.iter().enumerate() {
+            let trace = is_ordered_correctly(l, r);
+            let is_ordered = trace.verdict.unwrap();
+            if is_ordered {
+                res += idx + 1;
+            }
+            visualizer.set_title(&format!("Pair {}", idx + 1));
+            visualizer.set_stat("sum", &res);
+            visualize_comparison(visualizer, l, r, &trace);
+        }
+        res
     }
 
-    fn solve_pt2(
-        &self,
-        input: Self::InputT,
-        _visualizer: &mut dyn crate::visualizer::Visualizer,
-    ) -> Self::OutputT {
+    fn solve_pt2(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
         let divider_1 = Value::List(Vec::from([Value::List(Vec::from([Value::Int(2)]))]));
         let divider_2 = Value::List(Vec::from([Value::List(Vec::from([Value::Int(6)]))]));
 
@@ -193,8 +375,19 @@ impl Solution for DistressSignal {
             .collect();
         packets.push(divider_1.clone());
         packets.push(divider_2.clone());
+        setup_visualizer(visualizer);
+        visualizer.set_title("Sorting packets, deciding element of each comparison");
+        let mut comparisons: usize = 0;
         packets.sort_by(|a, b| {
-            if let Some(is_ordered_correctly_) = is_ordered_correctly(a, b) {
+            let trace = is_ordered_correctly(a, b);
+            comparisons += 1;
+            if visualizer.is_enabled() {
+                if let Some(step) = trace.deciding_step() {
+                    visualizer.set_stat("comparisons", &comparisons);
+                    visualize_step(visualizer, a, b, step, DECIDING_MARKS);
+                }
+            }
+            if let Some(is_ordered_correctly_) = trace.verdict {
                 if is_ordered_correctly_ {
                     Ordering::Less
                 } else {
@@ -1,6 +1,11 @@
-use crate::color::get_rgb_pixel;
+use crate::theme::Role;
 use crate::visualizer::CharVisualizationOption;
-use crate::{solution::Solution, types::Coords, visualizer::Visualizer};
+use crate::{
+    solution::Solution,
+    types::{Coords, InfiniteGrid},
+    viewport::Viewport,
+    visualizer::Visualizer,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Cell {
@@ -9,101 +14,127 @@ enum Cell {
     Sand,
 }
 
+#[derive(Clone, PartialEq)]
 enum ArrivedFrom {
     Above,
     Left,
     Right,
 }
 
+const SAND_SOURCE: Coords<i64> = Coords { x: 500, y: 0 };
+
 #[derive(Debug)]
 pub struct Cave {
-    map: Vec<Vec<Cell>>,
-    top_left: Coords<usize>,
-    width: usize,
-    height: usize,
+    map: InfiniteGrid<Cell>,
+    /// Inclusive corners of the scanned rocks, sand falling below them falls forever
+    rocks_bbox: (Coords<i64>, Coords<i64>),
+    has_floor: bool,
 }
 
 impl Cave {
-    fn new(top_left: Coords<usize>) -> Cave {
-        Cave {
-            map: Vec::new(),
-            top_left,
-            width: 0,
-            height: 0,
-        }
+    fn floor_y(&self) -> i64 {
+        self.rocks_bbox.1.y + 2
     }
 
-    fn coords2indices(&self, c: &Coords<usize>) -> (usize, usize) {
-        let c_rel = *c - self.top_left;
-        (c_rel.y, c_rel.x)
+    fn set(&mut self, c: &Coords<i64>, cell: Cell) {
+        self.map.set(*c, cell);
     }
 
-    fn set(&mut self, c: &Coords<usize>, cell: Cell) {
-        let (i, j) = self.coords2indices(c);
-        if j >= self.width {
-            for row in self.map.iter_mut() {
-                row.extend(vec![Cell::Air; j - self.width + 1]);
-            }
-            self.width = j + 1;
-        }
-        if i >= self.height {
-            (0..(i - self.height + 1)).for_each(|_| {
-                self.map.push(vec![Cell::Air; self.width]);
-            });
-            self.height = i + 1;
+    fn at(&self, c: &Coords<i64>) -> Cell {
+        if self.has_floor && c.y == self.floor_y() {
+            Cell::Rock
+        } else {
+            *self.map.get(*c)
         }
-        self.map[i][j] = cell;
     }
 
-    fn at(&self, c: &Coords<usize>) -> Cell {
-        let c_rel = *c - self.top_left;
-        let i = c_rel.y;
-        let j = c_rel.x;
-        self.map[i][j]
+    /// Inclusive corners of the part of the cave sand can get to: the rocks with a column of
+    /// air on each side, or with a floor the triangle sand piles up into
+    fn window(&self) -> (Coords<i64>, Coords<i64>) {
+        if self.has_floor {
+            let floor_y = self.floor_y();
+            (
+                Coords {
+                    x: SAND_SOURCE.x - floor_y - 1,
+                    y: 0,
+                },
+                Coords {
+                    x: SAND_SOURCE.x + floor_y + 1,
+                    y: floor_y,
+                },
+            )
+        } else {
+            let (min_corner, max_corner) = self.rocks_bbox;
+            (
+                Coords {
+                    x: min_corner.x - 1,
+                    y: 0,
+                },
+                Coords {
+                    x: max_corner.x + 1,
+                    y: max_corner.y,
+                },
+            )
+        }
     }
 
     fn setup_visualizer(v: &mut dyn Visualizer) {
         v.add_char_visualization_option(CharVisualizationOption {
             char: 'o',
             is_bold: false,
-            color: get_rgb_pixel(46, 100, 55),
+            role: Role::Fill,
         });
         v.add_char_visualization_option(CharVisualizationOption {
             char: '#',
             is_bold: true,
-            color: get_rgb_pixel(0, 100, 100),
+            role: Role::Wall,
         });
     }
 
     fn visualize(
         &self,
         v: &mut dyn Visualizer,
-        arrived_from_map: Option<&Vec<Vec<Option<ArrivedFrom>>>>,
+        arrived_from_map: Option<&InfiniteGrid<Option<ArrivedFrom>>>,
     ) {
         if !v.is_enabled() {
             return;
         }
-        for (i, row) in self.map.iter().enumerate() {
-            for (j, cell) in row.iter().enumerate() {
-                match cell {
-                    Cell::Rock => v.write_char('#'),
-                    Cell::Sand => v.write_char('o'),
-                    Cell::Air => {
-                        if let Some(arrived_from_map_) = arrived_from_map {
-                            match arrived_from_map_[i][j] {
-                                None => v.write_char(' '),
-                                Some(ArrivedFrom::Above) => v.write_char('↓'),
-                                Some(ArrivedFrom::Right) => v.write_char('↙'),
-                                Some(ArrivedFrom::Left) => v.write_char('↘'),
-                            }
-                        } else {
-                            v.write_char(' ');
-                        }
-                    }
-                };
+        let (min_corner, max_corner) = self.window();
+        let mut viewport = Viewport::new(
+            (max_corner.x - min_corner.x + 1) as usize,
+            (max_corner.y - min_corner.y + 1) as usize,
+        );
+        viewport.fit(min_corner, max_corner);
+        let mut canvas = match arrived_from_map {
+            Some(arrived_from_map_) => {
+                arrived_from_map_.draw(&viewport, ' ', |arrived_from| match arrived_from {
+                    None => ' ',
+                    Some(ArrivedFrom::Above) => '↓',
+                    Some(ArrivedFrom::Right) => '↙',
+                    Some(ArrivedFrom::Left) => '↘',
+                })
+            }
+            None => viewport.canvas(' '),
+        };
+        for (c, cell) in self.map.iter() {
+            match cell {
+                Cell::Rock => canvas.plot(c, '#'),
+                Cell::Sand => canvas.plot(c, 'o'),
+                Cell::Air => {}
+            }
+        }
+        if self.has_floor {
+            for x in min_corner.x..=max_corner.x {
+                canvas.plot(
+                    Coords {
+                        x,
+                        y: self.floor_y(),
+                    },
+                    '#',
+                );
             }
-            v.write_newline();
         }
+        canvas.write_to(v);
         v.end_frame();
     }
 }
@@ -115,36 +146,27 @@ impl Solution for RegolithReservoir {
     type OutputT = u32;
 
     fn parse_input(&self, input_raw: String) -> Self::InputT {
-        let mut rock_line_defs: Vec<Vec<Coords<usize>>> = Vec::new();
+        let mut rock_line_defs: Vec<Vec<Coords<i64>>> = Vec::new();
         for line in input_raw.lines() {
-            let mut rock_line_def: Vec<Coords<usize>> = Vec::new();
+            let mut rock_line_def: Vec<Coords<i64>> = Vec::new();
             for coord in line.split(" -> ") {
                 rock_line_def.push(coord.parse().unwrap());
             }
             rock_line_defs.push(rock_line_def);
         }
-        let top_left_x = rock_line_defs
-            .iter()
-            .flat_map(|r| r.iter().map(|c| c.x))
-            .min()
-            .unwrap()
-            .clone()
-            - 1; // -1 lets sand fall to the abyss
-        let top_left_y = 0;
-        let mut cave = Cave::new(Coords {
-            x: top_left_x,
-            y: top_left_y,
-        });
-
+        let mut map = InfiniteGrid::new(Cell::Air);
         for line_def in rock_line_defs {
             line_def
                 .windows(2)
-                .map(|window| window[0].line_between(&window[1]))
-                .flatten()
-                .for_each(|c| cave.set(&c, Cell::Rock));
+                .flat_map(|window| window[0].line_between(&window[1]))
+                .for_each(|c| map.set(c, Cell::Rock));
         }
 
-        cave
+        Cave {
+            rocks_bbox: map.bounding_box().expect("Cave must have rocks"),
+            map,
+            has_floor: false,
+        }
     }
 
     fn solve_pt1(
@@ -160,17 +182,13 @@ impl Solution for RegolithReservoir {
         let mut sand_particles: u32 = 0;
         loop {
             sand_particles += 1;
-            let mut current_sand_coords = Coords { x: 500, y: 0 };
-            let mut falling_frames = 0;
+            v.set_stat("sand_particles", &sand_particles);
+            let mut current_sand_coords = SAND_SOURCE;
             loop {
-                if current_sand_coords.y == input.top_left.y + input.height - 1 {
+                if current_sand_coords.y == input.rocks_bbox.1.y {
                     return sand_particles - 1;
                 }
-                falling_frames += 1;
-                if falling_frames % 5 == 0 {
-                    falling_frames = 0;
-                    input.visualize(v, None);
-                }
+                input.visualize(v, None);
                 input.set(&current_sand_coords, Cell::Sand);
                 let mut sand_moved = false;
                 for next_sand_coords in [
@@ -196,48 +214,11 @@ impl Solution for RegolithReservoir {
 
     fn solve_pt2(
         &self,
-        input: Self::InputT,
+        mut input: Self::InputT,
         v: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
-        let new_top_left = Coords {
-            x: 500 - input.height - 2,
-            y: 0,
-        };
-        let mut modified_input = Cave::new(new_top_left);
-        for (i, row) in input.map.iter().enumerate() {
-            for (j, cell) in row.iter().enumerate() {
-                modified_input.set(
-                    &Coords {
-                        x: input.top_left.x + j,
-                        y: input.top_left.y + i,
-                    },
-                    *cell,
-                );
-            }
-        }
-
-        for x in (500 - input.height - 2)..(500 + input.height + 2) {
-            modified_input.set(
-                &Coords {
-                    x,
-                    y: input.height + 1,
-                },
-                Cell::Rock,
-            );
-        }
-
-        let mut last_arrived_from: Vec<Vec<Option<ArrivedFrom>>> = Vec::new();
-        for _i in 0..modified_input.height {
-            let mut row: Vec<Option<ArrivedFrom>> = Vec::new();
-            for _j in 0..modified_input.width {
-                row.push(None);
-            }
-            last_arrived_from.push(row);
-        }
-
-        // modified_input.visualize(v);
-
-        // actual solution here
+        input.has_floor = true;
+        let mut last_arrived_from: InfiniteGrid<Option<ArrivedFrom>> = InfiniteGrid::new(None);
 
         Cave::setup_visualizer(v);
 
@@ -245,38 +226,36 @@ impl Solution for RegolithReservoir {
         let right = Coords { x: 1, y: 0 };
 
         let mut sand_particles: u32 = 0;
-        let sand_source_coords = Coords { x: 500, y: 0 };
-        let mut current_sand_coords = sand_source_coords;
+        let mut current_sand_coords = SAND_SOURCE;
         loop {
-            let (i, j) = modified_input.coords2indices(&current_sand_coords);
-            current_sand_coords = match last_arrived_from[i][j] {
-                None => sand_source_coords,
+            current_sand_coords = match last_arrived_from.get(current_sand_coords) {
+                None => SAND_SOURCE,
                 Some(ArrivedFrom::Above) => current_sand_coords - down,
                 Some(ArrivedFrom::Left) => current_sand_coords - down - right,
                 Some(ArrivedFrom::Right) => current_sand_coords - down + right,
             };
             sand_particles += 1;
+            v.set_stat("sand_particles", &sand_particles);
             loop {
-                modified_input.set(&current_sand_coords, Cell::Sand);
+                input.set(&current_sand_coords, Cell::Sand);
                 let mut sand_moved = false;
                 for (next_sand_coords, arrived_from) in [
                     (current_sand_coords + down, ArrivedFrom::Above),
                     (current_sand_coords + down - right, ArrivedFrom::Right),
                     (current_sand_coords + down + right, ArrivedFrom::Left),
                 ] {
-                    if modified_input.at(&next_sand_coords) == Cell::Air {
-                        modified_input.set(&current_sand_coords, Cell::Air);
+                    if input.at(&next_sand_coords) == Cell::Air {
+                        input.set(&current_sand_coords, Cell::Air);
                         current_sand_coords = next_sand_coords;
-                        modified_input.set(&current_sand_coords, Cell::Sand);
-                        let (i, j) = modified_input.coords2indices(&current_sand_coords);
-                        last_arrived_from[i][j] = Some(arrived_from);
+                        input.set(&current_sand_coords, Cell::Sand);
+                        last_arrived_from.set(current_sand_coords, Some(arrived_from));
                         sand_moved = true;
                         break;
                     }
                 }
                 if !sand_moved {
-                    modified_input.visualize(v, Some(&last_arrived_from));
-                    if current_sand_coords.x == 500 && current_sand_coords.y == 0 {
+                    input.visualize(v, Some(&last_arrived_from));
+                    if current_sand_coords == SAND_SOURCE {
                         return sand_particles;
                     } else {
                         break;
@@ -1,14 +1,205 @@
 use std::cmp::{max, min};
+use std::ops::Range;
 
 use crate::solution::Solution;
+use crate::theme::Role;
+use crate::types::{Coords, IntervalSet};
+use crate::viewport::Viewport;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
 
-use pathfinder_geometry::vector::Vector2I;
 use regex::Regex;
 
 #[derive(Debug)]
 pub struct Sensor {
-    position: Vector2I,
-    closest_beacon: Vector2I,
+    position: Coords<i64>,
+    closest_beacon: Coords<i64>,
+}
+
+impl Sensor {
+    fn range(&self) -> i64 {
+        self.position.manhattan_distance(&self.closest_beacon)
+    }
+
+    fn covers(&self, c: Coords<i64>) -> bool {
+        self.position.manhattan_distance(&c) <= self.range()
+    }
+
+    /// Same as `covered_xs`, as a set that's empty when the line misses the sensor's range
+    fn coverage_on(&self, line: &ScanLine) -> IntervalSet<i64> {
+        let mut coverage = IntervalSet::new();
+        coverage.insert(self.covered_xs(line));
+        coverage
+    }
+
+    /// X coordinates of the line's points within the sensor's range
+    fn covered_xs(&self, line: &ScanLine) -> Range<i64> {
+        let (x, y, range) = (self.position.x, self.position.y, self.range());
+        if line.slope == 0 {
+            let half_width = range - (y - line.y_at_x0).abs();
+            return x - half_width..x + half_width + 1;
+        }
+        // the points are at |x' - x| + |x' - crossing_x| <= range, where the line crosses the
+        // sensor's row at crossing_x
+        let crossing_x = (y - line.y_at_x0) * line.slope;
+        if (x - crossing_x).abs() > range {
+            return 0..0;
+        }
+        (x + crossing_x - range + 1).div_euclid(2)..(x + crossing_x + range).div_euclid(2) + 1
+    }
+}
+
+/// Horizontal or diagonal line of points at y = y_at_x0 + slope * x
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ScanLine {
+    y_at_x0: i64,
+    slope: i64,
+}
+
+impl ScanLine {
+    fn row(y: i64) -> ScanLine {
+        ScanLine {
+            y_at_x0: y,
+            slope: 0,
+        }
+    }
+
+    /// X coordinates of the line's points within the rectangle (inclusive corners)
+    fn x_range_within(&self, min_corner: Coords<i64>, max_corner: Coords<i64>) -> Range<i64> {
+        let x_range = min_corner.x..max_corner.x + 1;
+        let xs_in_y_range = match self.slope {
+            0 if min_corner.y <= self.y_at_x0 && self.y_at_x0 <= max_corner.y => x_range.clone(),
+            0 => 0..0,
+            1 => min_corner.y - self.y_at_x0..max_corner.y - self.y_at_x0 + 1,
+            -1 => self.y_at_x0 - max_corner.y..self.y_at_x0 - min_corner.y + 1,
+            _ => panic!("Unsupported slope {}", self.slope),
+        };
+        max(x_range.start, xs_in_y_range.start)..min(x_range.end, xs_in_y_range.end)
+    }
+
+    fn describe(&self) -> String {
+        match self.slope {
+            0 => format!("y = {}", self.y_at_x0),
+            1 => format!("y - x = {}", self.y_at_x0),
+            _ => format!("x + y = {}", self.y_at_x0),
+        }
+    }
+}
+
+/// Lines just out of range of each sensor along the edges of its diamond. The distress beacon
+/// is the only point not in range of any sensor, so its neighbors are in range and it lies on
+/// at least one of these
+fn lines_around_sensors(sensors: &[Sensor]) -> Vec<ScanLine> {
+    let mut lines = Vec::new();
+    for sensor in sensors {
+        let (x, y, outside) = (sensor.position.x, sensor.position.y, sensor.range() + 1);
+        for offset in [-outside, outside] {
+            lines.push(ScanLine {
+                y_at_x0: x + y + offset,
+                slope: -1,
+            });
+            lines.push(ScanLine {
+                y_at_x0: y - x + offset,
+                slope: 1,
+            });
+        }
+    }
+    lines.sort();
+    lines.dedup();
+    lines
+}
+
+fn coverage_on(sensors: &[Sensor], line: &ScanLine) -> IntervalSet<i64> {
+    let mut coverage = IntervalSet::new();
+    for sensor in sensors {
+        coverage.merge(&sensor.coverage_on(line));
+    }
+    coverage
+}
+
+const MAP_COLS: usize = 80;
+const MAP_ROWS: usize = 40;
+
+fn setup_visualizer(v: &mut dyn Visualizer) {
+    for (ch, role) in [
+        ('S', Role::Agent),
+        ('B', Role::Wall),
+        (':', Role::Visited),
+        ('#', Role::Fill),
+        ('-', Role::Frontier),
+        ('!', Role::Highlight),
+    ] {
+        v.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: ch != ':',
+            role,
+        });
+    }
+}
+
+/// Viewport fitted to all sensor diamonds
+fn overview_viewport(sensors: &[Sensor]) -> Viewport {
+    let mut min_corner = sensors[0].position;
+    let mut max_corner = min_corner;
+    for sensor in sensors {
+        let position = sensor.position;
+        let range = sensor.range();
+        min_corner.x = min(min_corner.x, position.x - range);
+        min_corner.y = min(min_corner.y, position.y - range);
+        max_corner.x = max(max_corner.x, position.x + range);
+        max_corner.y = max(max_corner.y, position.y + range);
+    }
+    let mut viewport = Viewport::new(MAP_COLS, MAP_ROWS);
+    viewport.fit(min_corner, max_corner);
+    viewport
+}
+
+/// Downsampled map: each cell shows the most important thing in the region it covers, sensor
+/// diamonds are sampled at cell centers. Parts of the scanned line in the coverage are
+/// beaconless.
+fn visualize_map(
+    v: &mut dyn Visualizer,
+    viewport: &Viewport,
+    sensors: &[Sensor],
+    scan: Option<(&ScanLine, &IntervalSet<i64>)>,
+    distress_beacon: Option<Coords<i64>>,
+) {
+    let is_within = |c: Coords<i64>, min_corner: Coords<i64>, max_corner: Coords<i64>| {
+        min_corner.x <= c.x && c.x <= max_corner.x && min_corner.y <= c.y && c.y <= max_corner.y
+    };
+    viewport.render(v, |min_corner, max_corner| {
+        if distress_beacon.is_some_and(|b| is_within(b, min_corner, max_corner)) {
+            return '!';
+        }
+        if sensors
+            .iter()
+            .any(|s| is_within(s.position, min_corner, max_corner))
+        {
+            return 'S';
+        }
+        if sensors
+            .iter()
+            .any(|s| is_within(s.closest_beacon, min_corner, max_corner))
+        {
+            return 'B';
+        }
+        if let Some((line, coverage)) = scan {
+            let xs = line.x_range_within(min_corner, max_corner);
+            if !xs.is_empty() {
+                return if coverage.overlaps(&xs) { '#' } else { '-' };
+            }
+        }
+        let center = Coords {
+            x: (min_corner.x + max_corner.x) / 2,
+            y: (min_corner.y + max_corner.y) / 2,
+        };
+        if sensors.iter().any(|s| s.covers(center)) {
+            ':'
+        } else {
+            ' '
+        }
+    });
+    v.set_stat("cell_size", &viewport.scale());
+    v.end_frame();
 }
 
 pub struct BeaconExclusionZone;
@@ -27,14 +218,14 @@ impl Solution for BeaconExclusionZone {
             .map(|l| {
                 let captures = line_re.captures_iter(l).next().unwrap();
                 Sensor {
-                    position: Vector2I::new(
-                        captures[1].parse::<i32>().unwrap(),
-                        captures[2].parse::<i32>().unwrap(),
-                    ),
-                    closest_beacon: Vector2I::new(
-                        captures[3].parse::<i32>().unwrap(),
-                        captures[4].parse::<i32>().unwrap(),
-                    ),
+                    position: Coords {
+                        x: captures[1].parse::<i64>().unwrap(),
+                        y: captures[2].parse::<i64>().unwrap(),
+                    },
+                    closest_beacon: Coords {
+                        x: captures[3].parse::<i64>().unwrap(),
+                        y: captures[4].parse::<i64>().unwrap(),
+                    },
                 }
             })
             .collect()
@@ -44,113 +235,113 @@ impl Solution for BeaconExclusionZone {
         sensors: Self::InputT,
         v: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
-        let y_scanning: i32 = if sensors.len() > 15 { 2000000 } else { 10 };
+        let y_scanning: i64 = if sensors.len() > 15 { 2000000 } else { 10 };
+        let line = ScanLine::row(y_scanning);
 
-        let mut beaconless_ranges: Vec<(i32, i32)> = Vec::new();
+        let mut beaconless = IntervalSet::new();
 
+        setup_visualizer(v);
+        let viewport = overview_viewport(&sensors);
+        v.set_title(&format!("Scanning y = {}", y_scanning));
         for (sensor_idx, sensor) in sensors.iter().enumerate() {
-            v.write_line(&format!(
-                "{}. at {:?}; closest beacon at {:?}",
-                sensor_idx + 1,
-                sensor.position,
-                sensor.closest_beacon
+            v.set_stat("sensor", &format!("{} / {}", sensor_idx + 1, sensors.len()));
+            v.log(&format!(
+                "At {:?}; closest beacon at {:?}",
+                sensor.position, sensor.closest_beacon
             ));
-            let delta = sensor.closest_beacon - sensor.position;
-            let sensor_range = delta.x().abs() + delta.y().abs();
-            let horiz_sensor_range = sensor_range - (sensor.position.y() - y_scanning).abs();
-            if horiz_sensor_range >= 0 {
-                let mut range_start = sensor.position.x() - horiz_sensor_range;
-                let mut range_end = sensor.position.x() + horiz_sensor_range + 1;
-                if sensor.closest_beacon.y() == y_scanning {
-                    if sensor.closest_beacon.x() == range_start {
-                        range_start += 1;
-                    }
-                    if sensor.closest_beacon.x() == range_end - 1 {
-                        range_end -= 1;
-                    }
-                }
-                if range_end > range_start {
-                    beaconless_ranges.push((range_start, range_end));
-                }
-                v.write_line(&format!("Ranges raw: {:?}", beaconless_ranges));
-
-                beaconless_ranges.sort_by(|a, b| (*a).0.cmp(&b.0));
-                v.write_line(&format!("Ranges sorted: {:?}", beaconless_ranges));
-                let mut i: usize = 0;
-                while i < beaconless_ranges.len() - 1 {
-                    if beaconless_ranges[i].1 < beaconless_ranges[i + 1].0 {
-                        i += 1;
-                    } else {
-                        let first = beaconless_ranges[i];
-                        let second = beaconless_ranges.remove(i + 1);
-                        beaconless_ranges[i] = (min(first.0, second.0), max(first.1, second.1));
-                    }
-                }
-                v.write_line(&format!("Ranges without overlaps: {:?}", beaconless_ranges));
+            let coverage = sensor.coverage_on(&line);
+            if coverage.is_empty() {
+                v.log("Does not impact the line we're scanning");
             } else {
-                v.write_line("Does not impact the line we're scanning");
+                beaconless.merge(&coverage);
+                v.log(&format!(
+                    "Ranges without overlaps: {:?}",
+                    beaconless.iter().collect::<Vec<_>>()
+                ));
+            }
+            if v.is_enabled() {
+                visualize_map(
+                    v,
+                    &viewport,
+                    &sensors[..=sensor_idx],
+                    Some((&line, &beaconless)),
+                    None,
+                );
+            }
+        }
+        for sensor in sensors.iter() {
+            if sensor.closest_beacon.y == y_scanning {
+                let x = sensor.closest_beacon.x;
+                beaconless.subtract(x..x + 1);
             }
-            v.end_frame();
         }
-        v.write_line(&format!("Ranges final: {:?}", beaconless_ranges));
 
-        beaconless_ranges.iter().map(|(f, b)| b - f).sum::<i32>() as u64
+        beaconless.total_length() as u64
     }
     fn solve_pt2(
         &self,
         sensors: Self::InputT,
-        _v: &mut dyn crate::visualizer::Visualizer,
+        v: &mut dyn crate::visualizer::Visualizer,
     ) -> Self::OutputT {
-        let max_coord = if sensors.len() > 15 { 4_000_000 } else { 20 };
-        let y_range = (0, max_coord);
-        let x_range = (0, max_coord);
-
-        let mut beaconless_ranges: Vec<(i32, i32)> = Vec::with_capacity(30);
-        for y_scanning in y_range.0..=y_range.1 {
-            beaconless_ranges.clear();
-            let mut new_beacon_found = true;
-            for sensor in sensors.iter() {
-                let delta = sensor.closest_beacon - sensor.position;
-                let sensor_range = delta.x().abs() + delta.y().abs();
-                let horiz_sensor_range = sensor_range - (sensor.position.y() - y_scanning).abs();
-                if horiz_sensor_range >= 0 {
-                    let range_start = sensor.position.x() - horiz_sensor_range;
-                    let range_end = sensor.position.x() + horiz_sensor_range + 1;
-                    if range_end > range_start {
-                        beaconless_ranges.push((range_start, range_end));
-                    }
+        let max_coord: i64 = if sensors.len() > 15 { 4_000_000 } else { 20 };
+        let search_min = Coords::origin();
+        let search_max = Coords {
+            x: max_coord,
+            y: max_coord,
+        };
+
+        setup_visualizer(v);
+        v.set_title(&format!(
+            "Searching 0..={} for the distress beacon",
+            max_coord
+        ));
+        let mut viewport = Viewport::new(MAP_COLS, MAP_ROWS);
+        viewport.fit(search_min, search_max);
 
-                    beaconless_ranges.sort_by(|a, b| (*a).0.cmp(&b.0));
+        let lines = lines_around_sensors(&sensors);
+        for (line_idx, line) in lines.iter().enumerate() {
+            let coverage = coverage_on(&sensors, line);
+            let gap = coverage
+                .gaps(line.x_range_within(search_min, search_max))
+                .next();
+
+            if v.is_enabled() {
+                v.set_stat("line", &format!("{} / {}", line_idx + 1, lines.len()));
+                v.log(&format!("Scanning {}", line.describe()));
+                visualize_map(v, &viewport, &sensors, Some((line, &coverage)), None);
+            }
 
-                    let mut i: usize = 0;
-                    while i < beaconless_ranges.len() - 1 {
-                        if beaconless_ranges[i].1 < beaconless_ranges[i + 1].0 {
-                            i += 1;
-                        } else {
-                            let first = beaconless_ranges[i];
-                            let second = beaconless_ranges.remove(i + 1);
-                            beaconless_ranges[i] = (min(first.0, second.0), max(first.1, second.1));
+            if let Some(gap) = gap {
+                let distress_beacon = Coords {
+                    x: gap.start,
+                    y: line.y_at_x0 + line.slope * gap.start,
+                };
+                if v.is_enabled() {
+                    v.set_title(&format!(
+                        "Distress beacon at x = {}, y = {}",
+                        distress_beacon.x, distress_beacon.y
+                    ));
+                    // zooming in twice per frame until every cell is a single point
+                    let mut half_side = max_coord / 2;
+                    loop {
+                        viewport.fit(
+                            Coords {
+                                x: distress_beacon.x - half_side,
+                                y: distress_beacon.y - half_side,
+                            },
+                            Coords {
+                                x: distress_beacon.x + half_side,
+                                y: distress_beacon.y + half_side,
+                            },
+                        );
+                        visualize_map(v, &viewport, &sensors, None, Some(distress_beacon));
+                        if viewport.scale() == 1 {
+                            break;
                         }
-                    }
-                    if beaconless_ranges
-                        .iter()
-                        .any(|(start, end)| *start <= x_range.0 && *end > x_range.1)
-                    {
-                        new_beacon_found = false;
-                        break;
+                        half_side /= 2;
                     }
                 }
-            }
-
-            if new_beacon_found {
-                let x_new_beacon = *beaconless_ranges
-                    .iter()
-                    .map(|(_, end)| end)
-                    .filter(|&end| &x_range.0 <= end && end <= &x_range.1)
-                    .next()
-                    .unwrap();
-                println!("{x_new_beacon}, {y_scanning}");
-                return (x_new_beacon as u64) * 4_000_000 + (y_scanning as u64);
+                return (distress_beacon.x as u64) * 4_000_000 + (distress_beacon.y as u64);
             }
         }
         0
@@ -1,5 +1,9 @@
+use crate::charts::sparkline;
+use crate::graph_export::{Graph, GraphExport};
 use crate::solution::Solution;
-use std::collections::{HashMap, HashSet};
+use crate::theme::Role;
+use crate::visualizer::{CharVisualizationOption, Visualizer};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use regex::Regex;
 
@@ -65,7 +69,7 @@ fn max_released_pressure(
     current_valve_name: &str,
     time_left: u32,
 ) -> u32 {
-    return (&travel_times[current_valve_name])
+    travel_times[current_valve_name]
         .iter()
         .filter_map(|(next_name, &travel_time)| {
             if travel_time + 1 > time_left {
@@ -78,7 +82,7 @@ fn max_released_pressure(
                 return None; // prune if we've already visited it in this branch
             }
 
-            return Some(
+            Some(
                 next.flow_rate * time_left_after_opening
                     + max_released_pressure(
                         valves,
@@ -87,15 +91,244 @@ fn max_released_pressure(
                         next_name,
                         time_left_after_opening,
                     ),
-            );
+            )
         })
         .max()
-        .unwrap_or(0);
+        .unwrap_or(0)
+}
+
+/// Valves to open in order, with time left after opening each. The search is walked again,
+/// following at each step a valve from which the known best released pressure is reachable.
+fn best_schedule<'a>(
+    valves: &'a Valves,
+    travel_times: &TravelTimes,
+    max_released: u32,
+) -> Vec<(&'a str, u32)> {
+    let mut schedule = Vec::new();
+    let mut opened_bitmask: u64 = 0;
+    let mut current_valve_name = "AA";
+    let mut time_left = MINUTE_COUNT as u32;
+    let mut released_left = max_released;
+    // only valves with a positive flow rate are worth traveling to, so each one opened releases
+    // some of the pressure left
+    while released_left > 0 {
+        let (next, time_left_after_opening, released_by_next) = travel_times[current_valve_name]
+            .iter()
+            .find_map(|(next_name, &travel_time)| {
+                if travel_time + 1 > time_left {
+                    return None;
+                }
+                let time_left_after_opening = time_left - travel_time - 1;
+                let next = &valves[next_name];
+                if (opened_bitmask & 1 << next.id) != 0 {
+                    return None;
+                }
+                let released_by_next = next.flow_rate * time_left_after_opening;
+                let released = released_by_next
+                    + max_released_pressure(
+                        valves,
+                        travel_times,
+                        opened_bitmask | 1 << next.id,
+                        next_name,
+                        time_left_after_opening,
+                    );
+                (released == released_left).then_some((
+                    next,
+                    time_left_after_opening,
+                    released_by_next,
+                ))
+            })
+            .expect("Best released pressure must be reachable");
+        schedule.push((next.name.as_str(), time_left_after_opening));
+        opened_bitmask |= 1 << next.id;
+        current_valve_name = &next.name;
+        time_left = time_left_after_opening;
+        released_left -= released_by_next;
+    }
+    schedule
+}
+
+/// Valves visited on the way, including both ends
+fn shortest_path(valves: &Valves, from: &str, to: &str) -> Vec<String> {
+    let mut came_from: HashMap<&str, &str> = HashMap::new();
+    let mut to_visit: VecDeque<&str> = VecDeque::from([from]);
+    while let Some(valve_name) = to_visit.pop_front() {
+        if valve_name == to {
+            break;
+        }
+        for neighbor_name in valves[valve_name].neighbor_names.iter() {
+            if neighbor_name != from && !came_from.contains_key(neighbor_name.as_str()) {
+                came_from.insert(neighbor_name, valve_name);
+                to_visit.push_back(neighbor_name);
+            }
+        }
+    }
+    let mut path = vec![to.to_string()];
+    let mut current = to;
+    while current != from {
+        current = came_from[current];
+        path.push(current.to_string());
+    }
+    path.reverse();
+    path
+}
+
+enum Action {
+    MoveTo(String),
+    Open(String),
+}
+
+/// What happens in each minute of the schedule (opened valves with minutes they are opened at)
+fn schedule_actions(valves: &Valves, schedule: &[(&str, u32)]) -> Vec<Action> {
+    let mut actions: Vec<Action> = Vec::new();
+    let mut current = "AA";
+    for (valve_name, _) in schedule {
+        for step in shortest_path(valves, current, valve_name)
+            .into_iter()
+            .skip(1)
+        {
+            actions.push(Action::MoveTo(step));
+        }
+        actions.push(Action::Open(valve_name.to_string()));
+        current = valve_name;
+    }
+    actions
+}
+
+fn valves_in_input_order(valves: &Valves) -> Vec<&Valve> {
+    let mut sorted: Vec<&Valve> = valves.values().collect();
+    sorted.sort_by_key(|v| v.id);
+    sorted
+}
+
+/// Tunnel graph with node size by flow rate; valves of the schedule and the tunnels walked
+/// between them are highlighted, with the minute each valve is opened at
+fn valves_graph(valves: &Valves, schedule: &[(&str, u32)]) -> Graph {
+    let open_minutes: HashMap<&str, u32> = schedule
+        .iter()
+        .map(|(name, time_left)| (*name, MINUTE_COUNT as u32 - time_left))
+        .collect();
+    let mut walked: HashSet<(String, String)> = HashSet::new();
+    let mut current = "AA";
+    for (valve_name, _) in schedule {
+        let path = shortest_path(valves, current, valve_name);
+        for pair in path.windows(2) {
+            walked.insert((pair[0].clone(), pair[1].clone()));
+            walked.insert((pair[1].clone(), pair[0].clone()));
+        }
+        current = valve_name;
+    }
+
+    let mut graph = Graph::new("valves", false);
+    for valve in valves_in_input_order(valves) {
+        let flow_rate = valve.flow_rate.to_string();
+        let (label_lines, color) = match open_minutes.get(valve.name.as_str()) {
+            Some(minute) => (
+                vec![valve.name.clone(), flow_rate, format!("open @{}", minute)],
+                "red",
+            ),
+            None if valve.name == "AA" => (vec![valve.name.clone(), "start".to_string()], "blue"),
+            None => (vec![valve.name.clone(), flow_rate], "black"),
+        };
+        let label_lines: Vec<&str> = label_lines.iter().map(|l| l.as_str()).collect();
+        let node = graph.add_node(&valve.name, &label_lines);
+        node.size = Some(0.6 + valve.flow_rate as f32 / 10.0);
+        node.color = Some(color.to_string());
+    }
+    for valve in valves_in_input_order(valves) {
+        for neighbor_name in valve.neighbor_names.iter() {
+            // tunnels go both ways, each one is listed for both valves
+            if valve.name < *neighbor_name {
+                let edge = graph.add_edge(&valve.name, neighbor_name);
+                if walked.contains(&(valve.name.clone(), neighbor_name.clone())) {
+                    edge.color = Some("red".to_string());
+                    edge.is_bold = true;
+                }
+            }
+        }
+    }
+    graph
+}
+
+fn setup_visualizer(v: &mut dyn Visualizer) {
+    for (ch, role) in [
+        ('[', Role::Agent),
+        (']', Role::Agent),
+        ('(', Role::Fill),
+        (')', Role::Fill),
+    ] {
+        v.add_char_visualization_option(CharVisualizationOption {
+            char: ch,
+            is_bold: true,
+            role,
+        });
+    }
+}
+
+// valves per line in the schedule replay
+const VALVES_PER_LINE: usize = 10;
+
+/// Frame per minute: current valve in [], opened ones in () with their flow rates
+fn visualize_schedule(v: &mut dyn Visualizer, valves: &Valves, schedule: &[(&str, u32)]) {
+    if !v.is_enabled() {
+        return;
+    }
+    let actions = schedule_actions(valves, schedule);
+    let mut current = "AA".to_string();
+    let mut opened: HashSet<String> = HashSet::new();
+    let mut pressure_per_minute = 0;
+    let mut released = 0;
+    let mut released_history: Vec<u64> = Vec::new();
+    v.set_title("Best schedule");
+    for minute in 1..=MINUTE_COUNT {
+        released += pressure_per_minute;
+        released_history.push(released as u64);
+        match actions.get(minute - 1) {
+            Some(Action::MoveTo(valve_name)) => {
+                v.log(&format!("move to {}", valve_name));
+                current = valve_name.clone();
+            }
+            Some(Action::Open(valve_name)) => {
+                let flow_rate = valves[valve_name].flow_rate;
+                v.log(&format!("open {} (flow rate {})", valve_name, flow_rate));
+                pressure_per_minute += flow_rate;
+                opened.insert(valve_name.clone());
+            }
+            None => v.log("wait"),
+        }
+
+        for (idx, valve) in valves_in_input_order(valves).iter().enumerate() {
+            let (open, close) = if valve.name == current {
+                ('[', ']')
+            } else if opened.contains(&valve.name) {
+                ('(', ')')
+            } else {
+                (' ', ' ')
+            };
+            v.write_str(&format!(
+                "{}{} {:>2}{} ",
+                open, valve.name, valve.flow_rate, close
+            ));
+            if idx % VALVES_PER_LINE == VALVES_PER_LINE - 1 {
+                v.write_newline();
+            }
+        }
+        v.write_newline();
+        v.write_line(&format!(
+            "Pressure released: {} {}",
+            sparkline(&released_history),
+            released
+        ));
+        v.set_stat("minute", &minute);
+        v.set_stat("pressure_per_minute", &pressure_per_minute);
+        v.set_stat("released", &released);
+        v.end_frame();
+    }
 }
 
 pub struct ProboscideaVolcanium;
 
-impl<'a> Solution for ProboscideaVolcanium {
+impl Solution for ProboscideaVolcanium {
     type InputT = Valves;
     type OutputT = u32;
 
@@ -122,21 +355,27 @@ impl<'a> Solution for ProboscideaVolcanium {
         res.iter().map(|v| (v.name.clone(), v.clone())).collect()
     }
 
-    fn solve_pt1(
-        &self,
-        input: Self::InputT,
-        _visualizer: &mut dyn crate::visualizer::Visualizer,
-    ) -> Self::OutputT {
+    fn solve_pt1(&self, input: Self::InputT, visualizer: &mut dyn Visualizer) -> Self::OutputT {
         let travel_times = calculate_travel_times(&input);
-        return max_released_pressure(&input, &travel_times, 0, "AA", 30);
+        let released = max_released_pressure(&input, &travel_times, 0, "AA", MINUTE_COUNT as u32);
+        if visualizer.is_enabled() {
+            let schedule = best_schedule(&input, &travel_times, released);
+            setup_visualizer(visualizer);
+            visualize_schedule(visualizer, &input, &schedule);
+        }
+        released
     }
 
-    fn solve_pt2(
-        &self,
-        _input: Self::InputT,
-        _v: &mut dyn crate::visualizer::Visualizer,
-    ) -> Self::OutputT {
-        return 0;
+    fn solve_pt2(&self, _input: Self::InputT, _v: &mut dyn Visualizer) -> Self::OutputT {
+        0
+    }
+}
+
+impl GraphExport for ProboscideaVolcanium {
+    fn to_graph(&self, input: &Self::InputT) -> Graph {
+        let travel_times = calculate_travel_times(input);
+        let released = max_released_pressure(input, &travel_times, 0, "AA", MINUTE_COUNT as u32);
+        valves_graph(input, &best_schedule(input, &travel_times, released))
     }
 }
 
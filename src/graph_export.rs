@@ -0,0 +1,192 @@
+use std::{fs, io, path::Path};
+
+use crate::solution::Solution;
+
+/// Solutions whose input is a graph worth looking at outside of the terminal
+pub trait GraphExport: Solution {
+    fn to_graph(&self, input: &Self::InputT) -> Graph;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz, `.dot` or `.gv`
+    Dot,
+    /// Mermaid flowchart, `.mmd` or `.mermaid`
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn from_path(path: &Path) -> Option<GraphFormat> {
+        match path.extension()?.to_str()? {
+            "dot" | "gv" => Some(GraphFormat::Dot),
+            "mmd" | "mermaid" => Some(GraphFormat::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub id: String,
+    pub label_lines: Vec<String>,
+    /// Diameter in inches, nodes with a size are drawn as circles
+    pub size: Option<f32>,
+    /// Color name understood by both Graphviz and CSS, like "red"
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub color: Option<String>,
+    pub is_bold: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+    pub name: String,
+    pub is_directed: bool,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    pub fn new(name: &str, is_directed: bool) -> Graph {
+        Graph {
+            name: name.to_string(),
+            is_directed,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Ids are used as is by both formats, so they should be plain alphanumeric
+    pub fn add_node(&mut self, id: &str, label_lines: &[&str]) -> &mut GraphNode {
+        self.nodes.push(GraphNode {
+            id: id.to_string(),
+            label_lines: label_lines.iter().map(|l| l.to_string()).collect(),
+            size: None,
+            color: None,
+        });
+        self.nodes.last_mut().unwrap()
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) -> &mut GraphEdge {
+        self.edges.push(GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            color: None,
+            is_bold: false,
+        });
+        self.edges.last_mut().unwrap()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let (keyword, edge_op) = if self.is_directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut dot = format!("{} {} {{\n", keyword, self.name);
+        for node in self.nodes.iter() {
+            let label = node
+                .label_lines
+                .iter()
+                .map(|l| l.replace('\\', "\\\\").replace('"', "\\\""))
+                .collect::<Vec<String>>()
+                .join("\\n");
+            let mut attrs = vec![format!("label=\"{}\"", label)];
+            if let Some(size) = node.size {
+                attrs.push(format!("shape=circle, fixedsize=true, width={:.1}", size));
+            }
+            if let Some(color) = node.color.as_ref() {
+                attrs.push(format!("color={}", color));
+            }
+            dot.push_str(&format!("  {} [{}];\n", node.id, attrs.join(", ")));
+        }
+        for edge in self.edges.iter() {
+            let mut attrs = Vec::new();
+            if let Some(color) = edge.color.as_ref() {
+                attrs.push(format!("color={}", color));
+            }
+            if edge.is_bold {
+                attrs.push("penwidth=3".to_string());
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            dot.push_str(&format!(
+                "  {} {} {}{};\n",
+                edge.from, edge_op, edge.to, attrs
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Mermaid has no node sizes, sized nodes are only drawn as circles
+    pub fn to_mermaid(&self) -> String {
+        let edge_op = if self.is_directed { "-->" } else { "---" };
+        let mut mermaid = format!("---\ntitle: {}\n---\nflowchart LR\n", self.name);
+        for node in self.nodes.iter() {
+            let label = node
+                .label_lines
+                .iter()
+                .map(|l| l.replace('"', "#quot;"))
+                .collect::<Vec<String>>()
+                .join("<br/>");
+            let (open, close) = if node.size.is_some() {
+                ("((", "))")
+            } else {
+                ("[", "]")
+            };
+            mermaid.push_str(&format!("  {}{}\"{}\"{}\n", node.id, open, label, close));
+        }
+        for edge in self.edges.iter() {
+            mermaid.push_str(&format!("  {} {} {}\n", edge.from, edge_op, edge.to));
+        }
+        for node in self.nodes.iter() {
+            if let Some(color) = node.color.as_ref() {
+                mermaid.push_str(&format!("  style {} stroke:{}\n", node.id, color));
+            }
+        }
+        // links can only be styled by their index in the order of definition
+        for (idx, edge) in self.edges.iter().enumerate() {
+            let mut styles = Vec::new();
+            if let Some(color) = edge.color.as_ref() {
+                styles.push(format!("stroke:{}", color));
+            }
+            if edge.is_bold {
+                styles.push("stroke-width:3px".to_string());
+            }
+            if !styles.is_empty() {
+                mermaid.push_str(&format!("  linkStyle {} {}\n", idx, styles.join(",")));
+            }
+        }
+        mermaid
+    }
+
+    pub fn write(&self, path: &Path, format: GraphFormat) -> io::Result<()> {
+        let contents = match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        };
+        fs::write(path, contents)
+    }
+}
+
+/// Parses the input and writes it as a graph
+pub fn export_graph<S: GraphExport>(
+    solution: &S,
+    input_raw: String,
+    path: &Path,
+    format: GraphFormat,
+) -> io::Result<()> {
+    let input = solution.parse_input(input_raw);
+    solution.to_graph(&input).write(path, format)?;
+    println!("Graph written to {}", path.display());
+    Ok(())
+}
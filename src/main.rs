@@ -1,27 +1,45 @@
+mod charts;
 mod color;
 mod days;
+mod graph_export;
 mod solution;
 mod text_to_image;
+mod theme;
 mod types;
 mod utils;
+mod viewport;
 mod visualizer;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use utils::read_input;
 
 use crate::{
+    graph_export::{export_graph, GraphFormat},
     solution::Solution,
-    visualizer::{DisabledVisualizer, GifVisualizer, TerminalVisualizer, Visualizer},
+    theme::Theme,
+    visualizer::{
+        html::HtmlVisualizer,
+        recording::{replay, RecordingVisualizer},
+        sampling::{FrameSampling, SamplingVisualizer},
+        serve::ServeVisualizer,
+        tee::TeeVisualizer,
+        DisabledVisualizer, GifVisualizer, TerminalVisualizer, Visualizer,
+    },
 };
 
 #[derive(Parser, Debug)]
 #[command(name = "AoC 2022 solutions")]
 #[command(author = "Igor V. <gosha.vaiman@gmail.com>")]
 #[command(version = "1.3.1.2")]
+#[command(subcommand_negates_reqs = true)]
 struct CliArgs {
-    day: u8,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(required = true)]
+    day: Option<u8>,
 
     #[arg(short, long, default_value_t = false)]
     example: bool,
@@ -29,50 +47,231 @@ struct CliArgs {
     #[arg(value_enum, default_value_t = types::RunPart::Both)]
     part: types::RunPart,
 
-    #[arg(short, long, default_value_t = false)]
+    /// Write the parsed input as a graph instead of solving, Graphviz DOT for .dot/.gv or
+    /// Mermaid for .mmd/.mermaid (days 7, 12 and 16)
+    #[arg(long, value_name = "FILE")]
+    export_graph: Option<PathBuf>,
+
+    #[command(flatten)]
+    vis: VisualizationArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show a recorded visualization with any of the backends, without running the solution
+    Replay {
+        #[arg(value_name = "FILE")]
+        recording: PathBuf,
+
+        /// Show frames with the same delays as the solution produced them, instead of --fps
+        #[arg(long, default_value_t = false)]
+        original_timing: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+struct VisualizationArgs {
+    #[arg(short, long, global = true, default_value_t = false)]
     visualize: bool,
 
-    #[arg(long, default_value_t = 30.0)]
+    #[arg(long, global = true, default_value_t = 30.0)]
     fps: f32,
 
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, global = true, default_value_t = false)]
     interactive: bool,
 
-    #[arg(long, value_name = "FILE")]
+    /// Show frames in the terminal even when they are written to a file or served
+    #[arg(short, long, global = true, default_value_t = false)]
+    terminal: bool,
+
+    #[arg(long, global = true, value_name = "FILE")]
     gif: Option<PathBuf>,
 
-    #[arg(long, default_value_t = 800)]
+    #[arg(long, global = true, default_value_t = 800)]
     gif_width: u32,
+
+    /// Colors of visualized chars and backgrounds
+    #[arg(long, global = true, value_enum, default_value_t = Theme::Dark)]
+    theme: Theme,
+
+    /// Write frames into a self-contained HTML player
+    #[arg(long, global = true, value_name = "FILE")]
+    html: Option<PathBuf>,
+
+    /// Stream frames to a browser at http://127.0.0.1:PORT/
+    #[arg(long, global = true, value_name = "PORT")]
+    serve: Option<u16>,
+
+    /// Keep serving the frames after the solution is done, until Ctrl-C
+    #[arg(long, global = true, default_value_t = false)]
+    keep_serving: bool,
+
+    /// Record frames to be shown later with the replay command
+    #[arg(long, global = true, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Show only every N-th frame
+    #[arg(long, global = true, value_name = "N", default_value_t = 1)]
+    every: usize,
+
+    /// Stop showing frames after this many were shown
+    #[arg(long, global = true, value_name = "N")]
+    max_frames: Option<usize>,
+
+    /// First frame to show (frames are numbered from 1)
+    #[arg(long, global = true, value_name = "N", default_value_t = 1)]
+    from_frame: usize,
+
+    /// Last frame to show
+    #[arg(long, global = true, value_name = "N")]
+    to_frame: Option<usize>,
+
+    /// Show at most one frame per this many milliseconds of solver time
+    #[arg(long, global = true, value_name = "MSEC")]
+    sample_ms: Option<u64>,
+}
+
+fn build_visualizer(args: &VisualizationArgs) -> Box<dyn Visualizer> {
+    if !args.visualize {
+        return Box::new(DisabledVisualizer {});
+    }
+
+    let show_in_terminal = (args.gif.is_none()
+        && args.html.is_none()
+        && args.serve.is_none()
+        && args.record.is_none())
+        || args.terminal
+        || args.interactive;
+    let mut backends: Vec<Box<dyn Visualizer>> = Vec::new();
+    if let Some(gif_path) = args.gif.as_ref() {
+        let gif_vis = GifVisualizer::new(
+            gif_path.to_str().unwrap(),
+            args.fps,
+            args.gif_width,
+            args.theme,
+        );
+        // progress dots would be drawn over the terminal frames
+        backends.push(Box::new(if show_in_terminal {
+            gif_vis.without_progress()
+        } else {
+            gif_vis
+        }));
+    }
+    if let Some(html_path) = args.html.as_ref() {
+        backends.push(Box::new(HtmlVisualizer::new(
+            html_path.to_str().unwrap(),
+            args.fps,
+            args.theme,
+        )));
+    }
+    if let Some(record_path) = args.record.as_ref() {
+        backends.push(Box::new(RecordingVisualizer::new(
+            record_path.to_str().unwrap(),
+        )));
+    }
+    if show_in_terminal {
+        backends.push(Box::new(TerminalVisualizer::new(
+            args.fps,
+            args.interactive,
+            args.theme,
+        )));
+    }
+    if let Some(port) = args.serve {
+        backends.push(Box::new(ServeVisualizer::new(port, args.fps, args.theme)));
+    }
+    let mut vis: Box<dyn Visualizer> = if backends.len() == 1 {
+        backends.pop().unwrap()
+    } else {
+        Box::new(TeeVisualizer::new(backends))
+    };
+
+    let sampling = FrameSampling {
+        every: args.every,
+        max_frames: args.max_frames,
+        from_frame: args.from_frame,
+        to_frame: args.to_frame,
+        min_interval: args.sample_ms.map(Duration::from_millis),
+    };
+    if !sampling.is_trivial() {
+        vis = Box::new(SamplingVisualizer::new(vis, sampling));
+    }
+    vis
 }
 
 fn main() {
-    let args = CliArgs::parse();
-    println!("AoC 2022, day {}", args.day);
+    let mut args = CliArgs::parse();
+
+    if let Some(Command::Replay {
+        recording,
+        original_timing,
+    }) = args.command
+    {
+        // replaying without showing anything makes no sense
+        args.vis.visualize = true;
+        let mut vis = build_visualizer(&args.vis);
+        if let Err(e) = replay(recording.to_str().unwrap(), vis.as_mut(), original_timing) {
+            println!("Error replaying visualization ({})!", e);
+            return;
+        }
+        if args.vis.keep_serving && args.vis.serve.is_some() {
+            ServeVisualizer::wait();
+        }
+        return;
+    }
+
+    let day = args.day.unwrap();
+    println!("AoC 2022, day {}", day);
 
-    let read_input_result = read_input(args.day, args.example);
+    let read_input_result = read_input(day, args.example);
     if let Err(e) = read_input_result {
         println!("Error reading input file ({})!", e);
         return;
     }
     let input = read_input_result.unwrap();
-    let part = args.part;
 
-    let vis: Box<dyn Visualizer> = match args.visualize {
-        true => {
-            if let Some(gif_path) = args.gif {
-                Box::new(GifVisualizer::new(
-                    gif_path.to_str().unwrap(),
-                    args.fps,
-                    args.gif_width,
-                ))
-            } else {
-                Box::new(TerminalVisualizer::new(args.fps, args.interactive))
+    if let Some(graph_path) = args.export_graph {
+        let Some(format) = GraphFormat::from_path(&graph_path) else {
+            eprintln!(
+                "Unknown graph format of {}, use .dot, .gv, .mmd or .mermaid",
+                graph_path.display()
+            );
+            std::process::exit(2);
+        };
+        let export_result = match day {
+            7 => export_graph(
+                &days::day07::NoSpaceLeftOnDevice,
+                input,
+                &graph_path,
+                format,
+            ),
+            12 => export_graph(
+                &days::day12::HillClimbingAlgorithm,
+                input,
+                &graph_path,
+                format,
+            ),
+            16 => export_graph(
+                &days::day16::ProboscideaVolcanium,
+                input,
+                &graph_path,
+                format,
+            ),
+            _ => {
+                println!("Graph export is not implemented for this day");
+                return;
             }
+        };
+        if let Err(e) = export_result {
+            eprintln!("Error writing graph file ({})!", e);
+            std::process::exit(1);
         }
-        false => Box::new(DisabledVisualizer {}),
-    };
+        return;
+    }
 
-    match args.day {
+    let part = args.part;
+    let vis = build_visualizer(&args.vis);
+
+    match day {
         1 => days::day01::CalorieCouting.run(input, part, vis),
         2 => days::day02::RockPaperScissors.run(input, part, vis),
         3 => days::day03::RucksacksReorganization.run(input, part, vis),
@@ -91,6 +290,10 @@ fn main() {
         16 => days::day16::ProboscideaVolcanium.run(input, part, vis),
         _ => {
             println!("Solution is not yet implemented");
+            return;
         }
     }
+    if args.vis.keep_serving && args.vis.serve.is_some() {
+        ServeVisualizer::wait();
+    }
 }
@@ -15,6 +15,7 @@ pub trait Solution {
         if part == RunPart::Pt1 || part == RunPart::Both {
             let start_pt1 = Instant::now();
             let output_pt1 = self.solve_pt1(input, visualizer.as_mut());
+            visualizer.finish();
             println!(
                 "\nPart 1 solution (took {:.3} msec):\n{}",
                 start_pt1.elapsed().as_secs_f32() * 1000.0,
@@ -24,6 +25,7 @@ pub trait Solution {
         if part == RunPart::Pt2 || part == RunPart::Both {
             let start_pt2 = Instant::now();
             let output_pt2 = self.solve_pt2(input_clone, visualizer.as_mut());
+            visualizer.finish();
             println!(
                 "\nPart 2 solution (took {:.3} msec):\n{}",
                 start_pt2.elapsed().as_secs_f32() * 1000.0,
@@ -12,6 +12,7 @@ use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use rand::prelude::*;
 
+use crate::theme::Theme;
 use crate::utils::ascii_box;
 use crate::utils::repeated_char;
 use crate::visualizer::CharVisualizationOption;
@@ -53,7 +54,7 @@ impl CharMatrix {
         for line in self.lines.iter().take(height) {
             let current_width = line.chars().count();
             if current_width > width {
-                new_lines.push(line[0..width].to_string());
+                new_lines.push(line.chars().take(width).collect());
             } else {
                 let mut extended_line = line.to_string();
                 extended_line.extend(repeated_char(' ', width - current_width).chars());
@@ -69,6 +70,10 @@ impl CharMatrix {
 
         self.lines = new_lines;
     }
+
+    pub fn append(&mut self, other: CharMatrix) {
+        self.lines.extend(other.lines);
+    }
 }
 
 pub fn text_to_image(
@@ -77,6 +82,7 @@ pub fn text_to_image(
     char_aspect_ratio: f32,
     position_randomization_range_px: f32,
     char_opts: &Vec<CharVisualizationOption>,
+    theme: Theme,
 ) -> Option<image::RgbaImage> {
     let (text_width_chars_, text_height_chars_) = char_matrix.dimensions();
     if text_width_chars_ == 0 {
@@ -100,8 +106,13 @@ pub fn text_to_image(
     let width_px = char_width_px * text_width_chars;
     let height_px = char_height_px * text_height_chars;
 
-    let mut imgbuf: image::RgbaImage =
-        image::ImageBuffer::from_pixel(width_px, height_px, image::Rgba([0, 0, 0, 255]));
+    let background = theme.background();
+    let foreground = theme.foreground();
+    let mut imgbuf: image::RgbaImage = image::ImageBuffer::from_pixel(
+        width_px,
+        height_px,
+        image::Rgba([background[0], background[1], background[2], 255]),
+    );
 
     let mut rng = rand::thread_rng();
     let mut rand_i32 = || (position_randomization_range_px * (rng.gen::<f32>() - 0.5)) as i32;
@@ -147,17 +158,17 @@ pub fn text_to_image(
                             height_px as i32 - 1,
                         ) as u32,
                     );
-                    *pixel = if let Some(opt) = maybe_char_visualization_opt {
-                        let dimming = *pixel_value as u32;
-                        image::Rgba([
-                            (dimming * opt.color[0] as u32 / 255) as u8,
-                            (dimming * opt.color[1] as u32 / 255) as u8,
-                            (dimming * opt.color[2] as u32 / 255) as u8,
-                            255,
-                        ])
-                    } else {
-                        image::Rgba([*pixel_value, *pixel_value, *pixel_value, 255])
-                    }
+                    let color = maybe_char_visualization_opt
+                        .map(|opt| theme.color(opt.role))
+                        .unwrap_or(foreground);
+                    // glyph coverage blends char color over the background
+                    let coverage = *pixel_value as i32;
+                    let blend = |i: usize| {
+                        (background[i] as i32
+                            + (color[i] as i32 - background[i] as i32) * coverage / 255)
+                            as u8
+                    };
+                    *pixel = image::Rgba([blend(0), blend(1), blend(2), 255]);
                 }
             }
             canvas.pixels = vec![0; canvas.pixels.len()];
@@ -177,6 +188,7 @@ pub fn example() {
         1.0,
         1.0,
         &Vec::new(),
+        Theme::Dark,
     )
     .unwrap()
     .save("test.png")
@@ -0,0 +1,166 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use clap::ValueEnum;
+use image::Rgb;
+
+use crate::color::{
+    colormap::{palette, Colormap, Gradient, Interpolation},
+    get_rgb_pixel,
+};
+
+/// What a visualized char means, themes decide how it looks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Wall,
+    Agent,
+    Frontier,
+    Visited,
+    /// Material accumulating over time, like settled sand
+    Fill,
+    /// What the viewer should notice first, like the answer
+    Highlight,
+    /// One of several things of the same kind, like rope knots
+    Series(usize),
+    /// Scalar value in 0..1, like height or temperature
+    Heat(f32),
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Wall => write!(f, "wall"),
+            Role::Agent => write!(f, "agent"),
+            Role::Frontier => write!(f, "frontier"),
+            Role::Visited => write!(f, "visited"),
+            Role::Fill => write!(f, "fill"),
+            Role::Highlight => write!(f, "highlight"),
+            Role::Series(idx) => write!(f, "series:{}", idx),
+            Role::Heat(value) => write!(f, "heat:{}", value),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RoleParsingError;
+
+impl FromStr for Role {
+    type Err = RoleParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("series", idx)) => Ok(Role::Series(idx.parse().map_err(|_| RoleParsingError)?)),
+            Some(("heat", value)) => Ok(Role::Heat(value.parse().map_err(|_| RoleParsingError)?)),
+            Some(_) => Err(RoleParsingError),
+            None => match s {
+                "wall" => Ok(Role::Wall),
+                "agent" => Ok(Role::Agent),
+                "frontier" => Ok(Role::Frontier),
+                "visited" => Ok(Role::Visited),
+                "fill" => Ok(Role::Fill),
+                "highlight" => Ok(Role::Highlight),
+                _ => Err(RoleParsingError),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Theme {
+    Dark,
+    Light,
+    HighContrast,
+    /// Okabe-Ito palette, distinguishable with any kind of color blindness
+    ColorBlind,
+}
+
+const OKABE_ITO: [Rgb<u8>; 7] = [
+    Rgb([230, 159, 0]),
+    Rgb([86, 180, 233]),
+    Rgb([0, 158, 115]),
+    Rgb([240, 228, 66]),
+    Rgb([0, 114, 178]),
+    Rgb([213, 94, 0]),
+    Rgb([204, 121, 167]),
+];
+
+const HIGH_CONTRAST_SERIES: [Rgb<u8>; 6] = [
+    Rgb([255, 255, 0]),
+    Rgb([0, 255, 255]),
+    Rgb([255, 0, 255]),
+    Rgb([0, 255, 0]),
+    Rgb([255, 128, 0]),
+    Rgb([255, 255, 255]),
+];
+
+// built once, colors are looked up for every styled char of every frame
+static DARK_SERIES: LazyLock<Vec<Rgb<u8>>> = LazyLock::new(|| palette(9, 100, 50));
+static LIGHT_SERIES: LazyLock<Vec<Rgb<u8>>> = LazyLock::new(|| palette(9, 100, 35));
+static LIGHT_HEAT: LazyLock<Gradient> = LazyLock::new(|| {
+    Gradient::new(
+        &[get_rgb_pixel(220, 100, 40), get_rgb_pixel(0, 100, 40)],
+        Interpolation::Hsl,
+    )
+});
+
+impl Theme {
+    pub fn background(&self) -> Rgb<u8> {
+        match self {
+            Theme::Dark | Theme::HighContrast | Theme::ColorBlind => Rgb([0, 0, 0]),
+            Theme::Light => Rgb([255, 255, 255]),
+        }
+    }
+
+    /// Color for chars without a role
+    pub fn foreground(&self) -> Rgb<u8> {
+        match self {
+            Theme::Dark | Theme::ColorBlind => Rgb([220, 220, 220]),
+            Theme::Light => Rgb([30, 30, 30]),
+            Theme::HighContrast => Rgb([255, 255, 255]),
+        }
+    }
+
+    pub fn color(&self, role: Role) -> Rgb<u8> {
+        match (self, role) {
+            (Theme::Dark, Role::Wall) => Rgb([255, 255, 255]),
+            (Theme::Dark, Role::Agent) => get_rgb_pixel(0, 100, 65),
+            (Theme::Dark, Role::Frontier) => get_rgb_pixel(0, 100, 50),
+            (Theme::Dark, Role::Visited) => get_rgb_pixel(35, 100, 50),
+            (Theme::Dark, Role::Fill) => get_rgb_pixel(46, 100, 55),
+            (Theme::Dark, Role::Highlight) => get_rgb_pixel(120, 100, 50),
+            (Theme::Dark, Role::Series(idx)) => DARK_SERIES[idx % DARK_SERIES.len()],
+            // the darkest part of magma would disappear in the background
+            (Theme::Dark, Role::Heat(value)) => Colormap::Magma.at_within(value, 0.25, 1.0),
+
+            (Theme::Light, Role::Wall) => Rgb([0, 0, 0]),
+            (Theme::Light, Role::Agent) => get_rgb_pixel(0, 100, 40),
+            (Theme::Light, Role::Frontier) => get_rgb_pixel(210, 100, 40),
+            (Theme::Light, Role::Visited) => get_rgb_pixel(30, 100, 45),
+            (Theme::Light, Role::Fill) => get_rgb_pixel(40, 100, 35),
+            (Theme::Light, Role::Highlight) => get_rgb_pixel(120, 100, 30),
+            (Theme::Light, Role::Series(idx)) => LIGHT_SERIES[idx % LIGHT_SERIES.len()],
+            (Theme::Light, Role::Heat(value)) => LIGHT_HEAT.at(value),
+
+            (Theme::HighContrast, Role::Wall) => Rgb([255, 255, 255]),
+            (Theme::HighContrast, Role::Agent) => Rgb([255, 0, 255]),
+            (Theme::HighContrast, Role::Frontier) => Rgb([255, 255, 0]),
+            (Theme::HighContrast, Role::Visited) => Rgb([0, 255, 255]),
+            (Theme::HighContrast, Role::Fill) => Rgb([0, 255, 0]),
+            (Theme::HighContrast, Role::Highlight) => Rgb([255, 0, 0]),
+            (Theme::HighContrast, Role::Series(idx)) => {
+                HIGH_CONTRAST_SERIES[idx % HIGH_CONTRAST_SERIES.len()]
+            }
+            (Theme::HighContrast, Role::Heat(value)) => Colormap::Diverging.at(value),
+
+            (Theme::ColorBlind, Role::Wall) => Rgb([220, 220, 220]),
+            (Theme::ColorBlind, Role::Agent) => OKABE_ITO[5],
+            (Theme::ColorBlind, Role::Frontier) => OKABE_ITO[1],
+            (Theme::ColorBlind, Role::Visited) => OKABE_ITO[0],
+            (Theme::ColorBlind, Role::Fill) => OKABE_ITO[3],
+            (Theme::ColorBlind, Role::Highlight) => OKABE_ITO[6],
+            (Theme::ColorBlind, Role::Series(idx)) => OKABE_ITO[idx % OKABE_ITO.len()],
+            (Theme::ColorBlind, Role::Heat(value)) => Colormap::Viridis.at(value),
+        }
+    }
+}
@@ -1,11 +1,19 @@
 use clap::ValueEnum;
+use num::Signed;
 use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::iter::{successors, Sum};
 use std::ops::Add;
 use std::ops::AddAssign;
-use std::ops::RangeInclusive;
+use std::ops::Mul;
+use std::ops::Range;
 use std::ops::Sub;
+use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
+use crate::viewport::{Viewport, ViewportCanvas};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum RunPart {
     Pt1,
@@ -41,33 +49,58 @@ impl<ParsableT: FromStr> FromStr for Coords<ParsableT> {
     }
 }
 
-impl<NumericT: From<bool> + Eq + Ord + Copy> Coords<NumericT>
-where
-    RangeInclusive<NumericT>: Iterator<Item = NumericT>,
-{
-    pub fn is_origin(&self) -> bool {
-        return self.x == NumericT::from(false) && self.y == NumericT::from(false);
-    }
-
+impl<NumericT: From<bool>> Coords<NumericT> {
     pub fn origin() -> Coords<NumericT> {
         Coords {
             x: NumericT::from(false),
             y: NumericT::from(false),
         }
     }
+}
+
+impl<NumericT: Ord + Copy> Coords<NumericT> {
+    /// Each component clamped separately, so the result is the closest point of the rectangle
+    pub fn clamp(&self, min_corner: Coords<NumericT>, max_corner: Coords<NumericT>) -> Self {
+        Coords {
+            x: self.x.clamp(min_corner.x, max_corner.x),
+            y: self.y.clamp(min_corner.y, max_corner.y),
+        }
+    }
+}
+
+impl<NumericT: Signed + Ord + Copy + Debug> Coords<NumericT> {
+    pub fn manhattan_distance(&self, other: &Coords<NumericT>) -> NumericT {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// Number of king moves between the points
+    pub fn chebyshev_distance(&self, other: &Coords<NumericT>) -> NumericT {
+        max((self.x - other.x).abs(), (self.y - other.y).abs())
+    }
+
+    /// Sign of each component, i.e. the single (possibly diagonal) step towards the point
+    pub fn signum(&self) -> Self {
+        Coords {
+            x: self.x.signum(),
+            y: self.y.signum(),
+        }
+    }
 
+    /// Points from self to other, both included. Only horizontal, vertical and 45 degree
+    /// diagonal lines go through whole points, panics on any other
     pub fn line_between(&self, other: &Coords<NumericT>) -> Vec<Coords<NumericT>> {
-        if self.x == other.x {
-            let start_y = min(self.y, other.y);
-            let end_y = max(self.y, other.y);
-            (start_y..=end_y).map(|y| Coords { x: self.x, y }).collect()
-        } else if self.y == other.y {
-            let start_x = min(self.x, other.x);
-            let end_x = max(self.x, other.x);
-            (start_x..=end_x).map(|x| Coords { y: self.y, x }).collect()
-        } else {
-            Vec::new()
+        let delta = *other - *self;
+        if !(delta.x.is_zero() || delta.y.is_zero() || delta.x.abs() == delta.y.abs()) {
+            panic!("No straight line between {:?} and {:?}", self, other)
+        }
+        let step = delta.signum();
+        let mut line = vec![*self];
+        let mut current = *self;
+        while current != *other {
+            current = current + step;
+            line.push(current);
         }
+        line
     }
 }
 
@@ -97,3 +130,671 @@ impl<NumericT: AddAssign> AddAssign for Coords<NumericT> {
         self.y += rhs.y;
     }
 }
+impl<NumericT: Mul<Output = NumericT> + Copy> Mul<NumericT> for Coords<NumericT> {
+    type Output = Coords<NumericT>;
+
+    fn mul(self, rhs: NumericT) -> Coords<NumericT> {
+        Coords {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+/// Steps to the orthogonal neighbors: up, right, down, left (y axis pointing down)
+pub const NEIGHBOR_STEPS_4: [Coords<i64>; 4] = [
+    Coords { x: 0, y: -1 },
+    Coords { x: 1, y: 0 },
+    Coords { x: 0, y: 1 },
+    Coords { x: -1, y: 0 },
+];
+
+/// Rectangular grid of cells stored row by row; coords are x for the column and y for the
+/// row, with (0, 0) in the top left corner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Panics if the rows are not all of the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            panic!("Grid rows must all be of the same length");
+        }
+        Grid {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        }
+    }
+
+    /// One row per line of the text, the cell is made from the char and its coords
+    pub fn parse<F>(text: &str, mut parse_cell: F) -> Grid<T>
+    where
+        F: FnMut(Coords<usize>, char) -> T,
+    {
+        Grid::from_rows(
+            text.lines()
+                .enumerate()
+                .map(|(y, line)| {
+                    line.chars()
+                        .enumerate()
+                        .map(|(x, ch)| parse_cell(Coords { x, y }, ch))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, c: Coords<usize>) -> bool {
+        c.x < self.width && c.y < self.height
+    }
+
+    pub fn get(&self, c: Coords<usize>) -> Option<&T> {
+        if self.contains(c) {
+            Some(&self.cells[c.y * self.width + c.x])
+        } else {
+            None
+        }
+    }
+
+    /// Coords one step away, if they are still inside the grid
+    pub fn step(&self, c: Coords<usize>, step: Coords<i64>) -> Option<Coords<usize>> {
+        let x = usize::try_from(c.x as i64 + step.x).ok()?;
+        let y = usize::try_from(c.y as i64 + step.y).ok()?;
+        let next = Coords { x, y };
+        if self.contains(next) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    /// Up to 4 orthogonal neighbors inside the grid
+    pub fn neighbors4(&self, c: Coords<usize>) -> impl Iterator<Item = Coords<usize>> + '_ {
+        NEIGHBOR_STEPS_4
+            .iter()
+            .filter_map(move |step| self.step(c, *step))
+    }
+
+    /// Coords going from `from` (not included) in steps until the edge of the grid
+    pub fn line_of_sight(
+        &self,
+        from: Coords<usize>,
+        step: Coords<i64>,
+    ) -> impl Iterator<Item = Coords<usize>> + '_ {
+        successors(self.step(from, step), move |c| self.step(*c, step))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// All coords row by row
+    pub fn coords(&self) -> impl Iterator<Item = Coords<usize>> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |idx| Coords {
+            x: idx % width,
+            y: idx / width,
+        })
+    }
+
+    /// Cells with their coords, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coords<usize>, &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Index<Coords<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, c: Coords<usize>) -> &T {
+        self.get(c)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", c))
+    }
+}
+
+impl<T> IndexMut<Coords<usize>> for Grid<T> {
+    fn index_mut(&mut self, c: Coords<usize>) -> &mut T {
+        if !self.contains(c) {
+            panic!("{:?} is outside of the grid", c);
+        }
+        &mut self.cells[c.y * self.width + c.x]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+const CHUNK_SIDE: i64 = 32;
+
+/// Unbounded grid in all directions, negative coords included. Cells are stored in square
+/// chunks allocated on the first write into them, cells never written hold the default value.
+#[derive(Debug, Clone)]
+pub struct InfiniteGrid<T> {
+    chunks: HashMap<Coords<i64>, Vec<T>>,
+    default: T,
+    // inclusive corners of the written cells
+    bounding_box: Option<(Coords<i64>, Coords<i64>)>,
+}
+
+impl<T: Clone + PartialEq> InfiniteGrid<T> {
+    pub fn new(default: T) -> InfiniteGrid<T> {
+        InfiniteGrid {
+            chunks: HashMap::new(),
+            default,
+            bounding_box: None,
+        }
+    }
+
+    /// Chunk coords and index of the cell within the chunk
+    fn locate(c: Coords<i64>) -> (Coords<i64>, usize) {
+        let chunk = Coords {
+            x: c.x.div_euclid(CHUNK_SIDE),
+            y: c.y.div_euclid(CHUNK_SIDE),
+        };
+        let idx = c.y.rem_euclid(CHUNK_SIDE) * CHUNK_SIDE + c.x.rem_euclid(CHUNK_SIDE);
+        (chunk, idx as usize)
+    }
+
+    pub fn get(&self, c: Coords<i64>) -> &T {
+        let (chunk, idx) = Self::locate(c);
+        self.chunks
+            .get(&chunk)
+            .map_or(&self.default, |cells| &cells[idx])
+    }
+
+    pub fn set(&mut self, c: Coords<i64>, value: T) {
+        let (chunk, idx) = Self::locate(c);
+        let default = &self.default;
+        self.chunks
+            .entry(chunk)
+            .or_insert_with(|| vec![default.clone(); (CHUNK_SIDE * CHUNK_SIDE) as usize])[idx] =
+            value;
+        self.bounding_box = Some(match self.bounding_box {
+            None => (c, c),
+            Some((min_corner, max_corner)) => (
+                Coords {
+                    x: min(min_corner.x, c.x),
+                    y: min(min_corner.y, c.y),
+                },
+                Coords {
+                    x: max(max_corner.x, c.x),
+                    y: max(max_corner.y, c.y),
+                },
+            ),
+        });
+    }
+
+    /// Inclusive corners (min x, min y), (max x, max y) of all cells written so far, even if
+    /// they were set back to the default
+    pub fn bounding_box(&self) -> Option<(Coords<i64>, Coords<i64>)> {
+        self.bounding_box
+    }
+
+    /// Cells that differ from the default, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Coords<i64>, &T)> {
+        self.chunks.iter().flat_map(move |(chunk, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(move |(_, value)| **value != self.default)
+                .map(move |(idx, value)| {
+                    let idx = idx as i64;
+                    let c = Coords {
+                        x: chunk.x * CHUNK_SIDE + idx % CHUNK_SIDE,
+                        y: chunk.y * CHUNK_SIDE + idx / CHUNK_SIDE,
+                    };
+                    (c, value)
+                })
+        })
+    }
+
+    /// Plots the cells that differ from the default on a canvas of the viewport, more can be
+    /// drawn over them before writing it out. When a viewport cell covers several grid cells,
+    /// any of them may end up shown.
+    pub fn draw<'v, F>(
+        &self,
+        viewport: &'v Viewport,
+        background: char,
+        cell_char: F,
+    ) -> ViewportCanvas<'v>
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut canvas = viewport.canvas(background);
+        for (c, value) in self.iter() {
+            canvas.plot(c, cell_char(value));
+        }
+        canvas
+    }
+}
+
+/// Set of values stored as sorted, disjoint half-open intervals; intervals that overlap or
+/// touch are merged as they are inserted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Range<T>>,
+}
+
+impl<T: Ord + Copy> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Disjoint intervals of the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.intervals.iter()
+    }
+
+    /// From the smallest value to the largest one, gaps included
+    pub fn span(&self) -> Option<Range<T>> {
+        Some(self.intervals.first()?.start..self.intervals.last()?.end)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.intervals.partition_point(|r| r.end < range.start);
+        let last = self.intervals.partition_point(|r| r.start <= range.end);
+        let mut merged = range;
+        if first < last {
+            merged.start = min(merged.start, self.intervals[first].start);
+            merged.end = max(merged.end, self.intervals[last - 1].end);
+        }
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    /// Union with the other set
+    pub fn merge(&mut self, other: &IntervalSet<T>) {
+        for range in other.iter() {
+            self.insert(range.clone());
+        }
+    }
+
+    pub fn subtract(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.intervals.partition_point(|r| r.end <= range.start);
+        let last = self.intervals.partition_point(|r| r.start < range.end);
+        if first == last {
+            return;
+        }
+        let mut remains = Vec::new();
+        if self.intervals[first].start < range.start {
+            remains.push(self.intervals[first].start..range.start);
+        }
+        if self.intervals[last - 1].end > range.end {
+            remains.push(range.end..self.intervals[last - 1].end);
+        }
+        self.intervals.splice(first..last, remains);
+    }
+
+    /// The interval holding values from the given one on, if any
+    fn first_ending_after(&self, value: T) -> Option<&Range<T>> {
+        self.intervals
+            .get(self.intervals.partition_point(|r| r.end <= value))
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.first_ending_after(value)
+            .is_some_and(|r| r.start <= value)
+    }
+
+    /// Whether every value of the range is in the set
+    pub fn covers(&self, range: &Range<T>) -> bool {
+        range.is_empty()
+            || self
+                .first_ending_after(range.start)
+                .is_some_and(|r| r.start <= range.start && range.end <= r.end)
+    }
+
+    /// Whether any value of the range is in the set
+    pub fn overlaps(&self, range: &Range<T>) -> bool {
+        !range.is_empty()
+            && self
+                .first_ending_after(range.start)
+                .is_some_and(|r| r.start < range.end)
+    }
+
+    pub fn is_superset(&self, other: &IntervalSet<T>) -> bool {
+        other.iter().all(|r| self.covers(r))
+    }
+
+    pub fn intersects(&self, other: &IntervalSet<T>) -> bool {
+        other.iter().any(|r| self.overlaps(r))
+    }
+
+    /// Intervals of the bounds that are not in the set, in increasing order
+    pub fn gaps(&self, bounds: Range<T>) -> impl Iterator<Item = Range<T>> {
+        let mut gaps = Vec::new();
+        let mut gap_start = bounds.start;
+        for range in self.intervals.iter() {
+            if range.end <= bounds.start || range.start >= bounds.end {
+                continue;
+            }
+            if range.start > gap_start {
+                gaps.push(gap_start..range.start);
+            }
+            gap_start = max(gap_start, range.end);
+        }
+        if gap_start < bounds.end {
+            gaps.push(gap_start..bounds.end);
+        }
+        gaps.into_iter()
+    }
+}
+
+impl<T: Ord + Copy + Sub<Output = T> + Sum> IntervalSet<T> {
+    /// Number of values in the set
+    pub fn total_length(&self) -> T {
+        self.intervals.iter().map(|r| r.end - r.start).sum()
+    }
+}
+
+impl<T: Ord + Copy> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T: Ord + Copy> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(x: usize, y: usize) -> Coords<usize> {
+        Coords { x, y }
+    }
+
+    fn numbered_grid() -> Grid<u32> {
+        Grid::parse("123\n456", |_, ch| ch.to_digit(10).unwrap())
+    }
+
+    #[test]
+    fn grid_parses_rows_as_y() {
+        let grid = numbered_grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[c(2, 0)], 3);
+        assert_eq!(grid[c(0, 1)], 4);
+        assert_eq!(grid.get(c(3, 0)), None);
+        assert_eq!(grid.to_string(), "123\n456\n");
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_rejects_ragged_rows() {
+        Grid::from_rows(vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn grid_new_fills_cells() {
+        let grid = Grid::new(2, 3, 'x');
+        assert_eq!(grid.rows().count(), 3);
+        assert!(grid.iter().all(|(_, &cell)| cell == 'x'));
+    }
+
+    #[test]
+    fn grid_rows() {
+        let grid = numbered_grid();
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![&[1, 2, 3], &[4, 5, 6]]
+        );
+    }
+
+    #[test]
+    fn grid_neighbors_stay_inside() {
+        let grid = numbered_grid();
+        assert_eq!(
+            grid.neighbors4(c(0, 0)).collect::<Vec<_>>(),
+            vec![c(1, 0), c(0, 1)]
+        );
+        assert_eq!(grid.neighbors4(c(1, 1)).count(), 3);
+    }
+
+    #[test]
+    fn grid_line_of_sight_excludes_start() {
+        let grid = numbered_grid();
+        let line: Vec<_> = grid
+            .line_of_sight(c(2, 1), Coords { x: -1, y: 0 })
+            .collect();
+        assert_eq!(line, vec![c(1, 1), c(0, 1)]);
+        assert_eq!(
+            grid.line_of_sight(c(0, 0), Coords { x: 0, y: -1 }).count(),
+            0
+        );
+    }
+
+    fn p(x: i64, y: i64) -> Coords<i64> {
+        Coords { x, y }
+    }
+
+    #[test]
+    fn coords_distances() {
+        assert_eq!(p(1, -2).manhattan_distance(&p(-3, 1)), 7);
+        assert_eq!(p(1, -2).chebyshev_distance(&p(-3, 1)), 4);
+    }
+
+    #[test]
+    fn coords_signum_and_clamp() {
+        assert_eq!(p(-7, 0).signum(), p(-1, 0));
+        assert_eq!(p(3, 9).signum(), p(1, 1));
+        assert_eq!(p(-7, 5).clamp(p(-2, -2), p(2, 2)), p(-2, 2));
+        assert_eq!(p(1, 1).clamp(p(-2, -2), p(2, 2)), p(1, 1));
+        assert_eq!(p(2, 3) * -2, p(-4, -6));
+    }
+
+    #[test]
+    fn coords_straight_lines() {
+        assert_eq!(
+            p(2, 0).line_between(&p(0, 0)),
+            vec![p(2, 0), p(1, 0), p(0, 0)]
+        );
+        assert_eq!(p(0, 0).line_between(&p(0, 0)), vec![p(0, 0)]);
+        assert_eq!(
+            p(0, 0).line_between(&p(-2, 2)),
+            vec![p(0, 0), p(-1, 1), p(-2, 2)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn coords_straight_line_rejects_other_angles() {
+        p(0, 0).line_between(&p(1, 2));
+    }
+
+    #[test]
+    fn infinite_grid_keeps_cells_apart_across_chunk_edges() {
+        let mut grid = InfiniteGrid::new(0);
+        let edges = [-65, -64, -33, -32, -31, -1, 0, 1, 31, 32, 33];
+        for (ix, &x) in edges.iter().enumerate() {
+            for (iy, &y) in edges.iter().enumerate() {
+                grid.set(p(x, y), ix * edges.len() + iy + 1);
+            }
+        }
+        for (ix, &x) in edges.iter().enumerate() {
+            for (iy, &y) in edges.iter().enumerate() {
+                assert_eq!(*grid.get(p(x, y)), ix * edges.len() + iy + 1);
+            }
+        }
+        assert_eq!(*grid.get(p(-2, -1)), 0);
+        assert_eq!(*grid.get(p(-1000, 1000)), 0);
+    }
+
+    #[test]
+    fn infinite_grid_bounding_box() {
+        let mut grid = InfiniteGrid::new('.');
+        assert_eq!(grid.bounding_box(), None);
+        grid.set(p(-33, 5), '#');
+        assert_eq!(grid.bounding_box(), Some((p(-33, 5), p(-33, 5))));
+        grid.set(p(40, -1), '#');
+        grid.set(p(0, 0), '#');
+        assert_eq!(grid.bounding_box(), Some((p(-33, -1), p(40, 5))));
+        // cells set back to the default still count as written
+        grid.set(p(40, -1), '.');
+        assert_eq!(grid.bounding_box(), Some((p(-33, -1), p(40, 5))));
+    }
+
+    #[test]
+    fn infinite_grid_iterates_non_default_cells() {
+        let mut grid = InfiniteGrid::new(false);
+        assert_eq!(grid.iter().count(), 0);
+        for c in [p(-1, -1), p(-32, 0), p(5, -33), p(31, 31)] {
+            grid.set(c, true);
+        }
+        grid.set(p(31, 31), false);
+        let mut cells: Vec<_> = grid.iter().map(|(c, value)| (c, *value)).collect();
+        cells.sort_by_key(|(c, _)| (c.x, c.y));
+        assert_eq!(
+            cells,
+            vec![(p(-32, 0), true), (p(-1, -1), true), (p(5, -33), true)]
+        );
+    }
+
+    fn intervals(ranges: &[Range<i64>]) -> IntervalSet<i64> {
+        let mut set = IntervalSet::new();
+        for range in ranges {
+            set.insert(range.clone());
+        }
+        set
+    }
+
+    fn ranges(set: &IntervalSet<i64>) -> Vec<Range<i64>> {
+        set.iter().cloned().collect()
+    }
+
+    #[test]
+    fn interval_set_insert_merges_touching_and_overlapping() {
+        assert!(intervals(&[]).is_empty());
+        assert!(intervals(&[3..3, 5..5]).is_empty());
+        assert_eq!(ranges(&intervals(&[5..7, 0..2])), vec![0..2, 5..7]);
+        // touching ends are merged, the set is half-open
+        assert_eq!(ranges(&intervals(&[0..2, 2..4])), vec![0..4]);
+        assert_eq!(ranges(&intervals(&[2..4, 0..2])), vec![0..4]);
+        assert_eq!(ranges(&intervals(&[0..2, 3..4])), vec![0..2, 3..4]);
+        // one insert bridging several intervals
+        assert_eq!(
+            ranges(&intervals(&[0..2, 4..6, 8..10, 12..14, 1..9])),
+            vec![0..10, 12..14]
+        );
+        assert_eq!(ranges(&intervals(&[0..10, 3..5])), vec![0..10]);
+    }
+
+    #[test]
+    fn interval_set_subtract() {
+        let mut set = intervals(&[0..10, 20..30]);
+        set.subtract(3..5);
+        assert_eq!(ranges(&set), vec![0..3, 5..10, 20..30]);
+        // touching the ends removes nothing
+        set.subtract(10..20);
+        set.subtract(-5..0);
+        assert_eq!(ranges(&set), vec![0..3, 5..10, 20..30]);
+        set.subtract(2..21);
+        assert_eq!(ranges(&set), vec![0..2, 21..30]);
+        set.subtract(0..2);
+        assert_eq!(ranges(&set), vec![21..30]);
+        set.subtract(25..25);
+        assert_eq!(ranges(&set), vec![21..30]);
+        set.subtract(0..100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn interval_set_queries() {
+        let set = intervals(&[0..3, 5..8]);
+        assert!(set.contains(0) && set.contains(2) && set.contains(5));
+        assert!(!set.contains(3) && !set.contains(-1) && !set.contains(8));
+        assert!(set.covers(&(5..8)) && set.covers(&(4..4)));
+        assert!(!set.covers(&(2..6)));
+        assert!(set.overlaps(&(2..6)) && set.overlaps(&(7..100)));
+        assert!(!set.overlaps(&(3..5)) && !set.overlaps(&(1..1)));
+        assert!(set.is_superset(&intervals(&[1..2, 6..8])));
+        assert!(!set.is_superset(&intervals(&[1..2, 2..4])));
+        assert!(set.intersects(&intervals(&[-5..0, 7..9])));
+        assert!(!set.intersects(&intervals(&[-5..0, 3..5, 8..9])));
+        assert_eq!(set.total_length(), 6);
+        assert_eq!(set.span(), Some(0..8));
+        assert_eq!(intervals(&[]).span(), None);
+    }
+
+    #[test]
+    fn interval_set_gaps() {
+        let set = intervals(&[0..3, 5..8, 12..15]);
+        assert_eq!(
+            set.gaps(-2..20).collect::<Vec<_>>(),
+            vec![-2..0, 3..5, 8..12, 15..20]
+        );
+        assert_eq!(set.gaps(1..6).collect::<Vec<_>>(), vec![3..5]);
+        assert_eq!(set.gaps(3..5).collect::<Vec<_>>(), vec![3..5]);
+        assert_eq!(set.gaps(5..8).count(), 0);
+        assert_eq!(intervals(&[]).gaps(0..4).collect::<Vec<_>>(), vec![0..4]);
+    }
+
+    #[test]
+    fn interval_set_merge() {
+        let mut set = intervals(&[0..2, 10..12]);
+        set.merge(&intervals(&[2..4, 6..8, 11..15]));
+        assert_eq!(ranges(&set), vec![0..4, 6..8, 10..15]);
+    }
+}
@@ -0,0 +1,311 @@
+use std::cmp::{max, min};
+
+use crate::{types::Coords, visualizer::Visualizer};
+
+/// Camera over an unbounded world with integer coordinates. The visible part of the world
+/// is mapped onto a fixed grid of cells, each cell covering `scale` x `scale` world points,
+/// so huge worlds are downsampled instead of producing huge frames.
+pub struct Viewport {
+    cols: usize,
+    rows: usize,
+    // world coordinates of the first point in the top left cell; with y axis pointing up
+    // that's the cell's smallest x and largest y
+    top_left: Coords<i64>,
+    scale: i64,
+    is_y_up: bool,
+}
+
+impl Viewport {
+    pub fn new(cols: usize, rows: usize) -> Viewport {
+        let mut viewport = Viewport {
+            cols: max(cols, 1),
+            rows: max(rows, 1),
+            top_left: Coords::origin(),
+            scale: 1,
+            is_y_up: false,
+        };
+        viewport.center_on(Coords::origin());
+        viewport
+    }
+
+    /// Rows are drawn from the largest y to the smallest, as on a math plot
+    pub fn y_up(mut self) -> Viewport {
+        let center = self.center();
+        self.is_y_up = true;
+        self.center_on(center);
+        self
+    }
+
+    /// Size of the world region covered by one cell along each axis
+    pub fn scale(&self) -> i64 {
+        self.scale
+    }
+
+    fn width(&self) -> i64 {
+        self.cols as i64 * self.scale
+    }
+
+    fn height(&self) -> i64 {
+        self.rows as i64 * self.scale
+    }
+
+    pub fn center(&self) -> Coords<i64> {
+        Coords {
+            x: self.top_left.x + self.width() / 2,
+            y: if self.is_y_up {
+                self.top_left.y - self.height() / 2
+            } else {
+                self.top_left.y + self.height() / 2
+            },
+        }
+    }
+
+    pub fn center_on(&mut self, target: Coords<i64>) {
+        self.top_left = Coords {
+            x: target.x - self.width() / 2,
+            y: if self.is_y_up {
+                target.y + self.height() / 2
+            } else {
+                target.y - self.height() / 2
+            },
+        };
+    }
+
+    /// Pans the viewport just enough to keep the target at least `margin` cells away from the edges
+    pub fn follow(&mut self, target: Coords<i64>, margin: usize) {
+        let margin_x = margin.min((self.cols - 1) / 2) as i64;
+        let margin_y = margin.min((self.rows - 1) / 2) as i64;
+        let cell = self.cell_of_unbounded(target);
+        let kept_cell = cell.clamp(
+            Coords {
+                x: margin_x,
+                y: margin_y,
+            },
+            Coords {
+                x: self.cols as i64 - 1 - margin_x,
+                y: self.rows as i64 - 1 - margin_y,
+            },
+        );
+        let shift = cell - kept_cell;
+        self.top_left.x += shift.x * self.scale;
+        if self.is_y_up {
+            self.top_left.y -= shift.y * self.scale;
+        } else {
+            self.top_left.y += shift.y * self.scale;
+        }
+    }
+
+    /// Picks the smallest scale at which the bounding box (inclusive corners) is fully visible
+    /// and centers on it
+    pub fn fit(&mut self, min_corner: Coords<i64>, max_corner: Coords<i64>) {
+        let bbox_width = (max_corner.x - min_corner.x).abs() + 1;
+        let bbox_height = (max_corner.y - min_corner.y).abs() + 1;
+        let ceil_div = |a: i64, b: i64| (a + b - 1) / b;
+        self.scale = max(
+            max(
+                ceil_div(bbox_width, self.cols as i64),
+                ceil_div(bbox_height, self.rows as i64),
+            ),
+            1,
+        );
+        // the margins are split directly rather than through `center_on`, which would cut off the
+        // last column or row of an even sized box at scale 1
+        let min_x = min(min_corner.x, max_corner.x);
+        let (min_y, max_y) = (
+            min(min_corner.y, max_corner.y),
+            max(min_corner.y, max_corner.y),
+        );
+        let margin_x = (self.width() - bbox_width) / 2;
+        let margin_y = (self.height() - bbox_height) / 2;
+        self.top_left = Coords {
+            x: min_x - margin_x,
+            y: if self.is_y_up {
+                max_y + margin_y
+            } else {
+                min_y - margin_y
+            },
+        };
+    }
+
+    fn cell_of_unbounded(&self, c: Coords<i64>) -> Coords<i64> {
+        Coords {
+            x: (c.x - self.top_left.x).div_euclid(self.scale),
+            y: if self.is_y_up {
+                (self.top_left.y - c.y).div_euclid(self.scale)
+            } else {
+                (c.y - self.top_left.y).div_euclid(self.scale)
+            },
+        }
+    }
+
+    /// Cell (column and row) the world point falls into, if it's visible
+    pub fn cell_of(&self, c: Coords<i64>) -> Option<Coords<usize>> {
+        let cell = self.cell_of_unbounded(c);
+        if cell.x < 0 || cell.y < 0 || cell.x >= self.cols as i64 || cell.y >= self.rows as i64 {
+            None
+        } else {
+            Some(Coords {
+                x: cell.x as usize,
+                y: cell.y as usize,
+            })
+        }
+    }
+
+    /// Inclusive corners of the world region covered by the cell: (min x, min y), (max x, max y)
+    pub fn cell_bounds(&self, cell: Coords<usize>) -> (Coords<i64>, Coords<i64>) {
+        let x_min = self.top_left.x + cell.x as i64 * self.scale;
+        let (y_min, y_max) = if self.is_y_up {
+            let y_max = self.top_left.y - cell.y as i64 * self.scale;
+            (y_max - self.scale + 1, y_max)
+        } else {
+            let y_min = self.top_left.y + cell.y as i64 * self.scale;
+            (y_min, y_min + self.scale - 1)
+        };
+        (
+            Coords { x: x_min, y: y_min },
+            Coords {
+                x: x_min + self.scale - 1,
+                y: y_max,
+            },
+        )
+    }
+
+    /// Writes a frame, asking for the char of each cell given the world region it covers
+    pub fn render<F>(&self, vis: &mut dyn Visualizer, mut cell_char: F)
+    where
+        F: FnMut(Coords<i64>, Coords<i64>) -> char,
+    {
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (min_corner, max_corner) = self.cell_bounds(Coords { x: col, y: row });
+                vis.write_char(cell_char(min_corner, max_corner));
+            }
+            vis.write_newline();
+        }
+    }
+
+    /// Blank cell grid to plot points on, for worlds that are easier to draw point by point
+    pub fn canvas(&self, background: char) -> ViewportCanvas<'_> {
+        ViewportCanvas {
+            viewport: self,
+            cells: vec![vec![background; self.cols]; self.rows],
+        }
+    }
+}
+
+pub struct ViewportCanvas<'v> {
+    viewport: &'v Viewport,
+    cells: Vec<Vec<char>>,
+}
+
+impl ViewportCanvas<'_> {
+    /// Points outside of the viewport are ignored, later plots overwrite earlier ones
+    pub fn plot(&mut self, c: Coords<i64>, ch: char) {
+        if let Some(cell) = self.viewport.cell_of(c) {
+            self.cells[cell.y][cell.x] = ch;
+        }
+    }
+
+    pub fn write_to(&self, vis: &mut dyn Visualizer) {
+        for row in self.cells.iter() {
+            for ch in row {
+                vis.write_char(*ch);
+            }
+            vis.write_newline();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(x: i64, y: i64) -> Coords<i64> {
+        Coords { x, y }
+    }
+
+    fn cell(x: usize, y: usize) -> Option<Coords<usize>> {
+        Some(Coords { x, y })
+    }
+
+    #[test]
+    fn fit_shows_the_whole_box() {
+        // even sized box filling the viewport
+        let mut viewport = Viewport::new(4, 2);
+        viewport.fit(p(10, 20), p(13, 21));
+        assert_eq!(viewport.scale(), 1);
+        assert_eq!(viewport.cell_of(p(10, 20)), cell(0, 0));
+        assert_eq!(viewport.cell_of(p(13, 21)), cell(3, 1));
+
+        // even sized box, centered with the extra column on the right
+        let mut viewport = Viewport::new(5, 5);
+        viewport.fit(p(0, 0), p(1, 1));
+        assert_eq!(viewport.scale(), 1);
+        assert_eq!(viewport.cell_of(p(0, 0)), cell(1, 1));
+        assert_eq!(viewport.cell_of(p(1, 1)), cell(2, 2));
+
+        // odd sized box, corners given in any order
+        let mut viewport = Viewport::new(5, 5);
+        viewport.fit(p(-8, 2), p(-10, 0));
+        assert_eq!(viewport.scale(), 1);
+        assert_eq!(viewport.cell_of(p(-10, 0)), cell(1, 1));
+        assert_eq!(viewport.cell_of(p(-8, 2)), cell(3, 3));
+    }
+
+    #[test]
+    fn fit_downsamples_large_boxes() {
+        let mut viewport = Viewport::new(4, 4);
+        viewport.fit(p(0, 0), p(9, 3));
+        assert_eq!(viewport.scale(), 3);
+        assert_eq!(viewport.cell_of(p(0, 0)).map(|c| c.x), Some(0));
+        assert_eq!(viewport.cell_of(p(9, 3)).map(|c| c.x), Some(3));
+
+        let mut viewport = Viewport::new(80, 40);
+        viewport.fit(p(0, 0), p(4_000_000, 4_000_000));
+        assert_eq!(viewport.scale(), 100_001);
+        assert!(viewport.cell_of(p(0, 0)).is_some());
+        assert!(viewport.cell_of(p(4_000_000, 4_000_000)).is_some());
+    }
+
+    #[test]
+    fn fit_with_y_up_puts_the_largest_y_on_top() {
+        let mut viewport = Viewport::new(4, 4).y_up();
+        viewport.fit(p(0, 0), p(3, 3));
+        assert_eq!(viewport.cell_of(p(0, 3)), cell(0, 0));
+        assert_eq!(viewport.cell_of(p(3, 0)), cell(3, 3));
+        assert_eq!(
+            viewport.cell_bounds(Coords { x: 0, y: 0 }),
+            (p(0, 3), p(0, 3))
+        );
+    }
+
+    #[test]
+    fn follow_keeps_the_target_within_the_margin() {
+        let mut viewport = Viewport::new(10, 6);
+        let margin = 2;
+        let start = viewport.cell_of(p(0, 0));
+        viewport.follow(p(0, 0), margin);
+        assert_eq!(viewport.cell_of(p(0, 0)), start);
+
+        // one step past the margin pans by one cell
+        viewport.follow(p(3, 0), margin);
+        assert_eq!(viewport.cell_of(p(3, 0)).map(|c| c.x), Some(7));
+
+        for target in [p(100, 0), p(100, 100), p(-50, -3), p(-51, -3), p(0, 0)] {
+            viewport.follow(target, margin);
+            let c = viewport.cell_of(target).unwrap();
+            assert!((2..=7).contains(&c.x) && (2..=3).contains(&c.y), "{:?}", c);
+        }
+        // panning only as far as needed
+        assert_eq!(viewport.cell_of(p(0, 0)), cell(7, 3));
+    }
+
+    #[test]
+    fn follow_caps_the_margin_at_half_the_viewport() {
+        let mut viewport = Viewport::new(5, 5);
+        for target in [p(20, 20), p(-7, 3)] {
+            viewport.follow(target, 100);
+            assert_eq!(viewport.cell_of(target), cell(2, 2));
+        }
+    }
+}
@@ -1,26 +1,107 @@
 use std::{
+    cmp::max,
     collections::VecDeque,
+    fmt::Display,
     fs::File,
     io::{self, Write},
+    sync::Once,
     thread::sleep,
     time::Duration,
 };
 
-use image::{codecs::gif::GifEncoder, Delay, Frame, Rgb};
+use image::{codecs::gif::GifEncoder, Delay, Frame};
 
 use termion::event::Key;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
 
+pub mod html;
+pub mod recording;
+pub mod sampling;
+pub mod serve;
+pub mod tee;
+
 use crate::{
+    color::colormap::to_ansi_colour,
     text_to_image::{text_to_image, CharMatrix},
+    theme::{Role, Theme},
     types::Coords,
+    utils::repeated_char,
 };
 
+#[derive(Clone)]
 pub struct CharVisualizationOption {
     pub char: char,
     pub is_bold: bool,
-    pub color: Rgb<u8>,
+    pub role: Role,
+}
+
+/// Structured information shown alongside the frame: title and stats persist until changed,
+/// log messages are attached to the frame they were logged in
+#[derive(Debug, Default, Clone)]
+pub struct FrameAnnotations {
+    pub title: Option<String>,
+    pub stats: Vec<(String, String)>,
+    pub log: Vec<String>,
+}
+
+impl FrameAnnotations {
+    pub fn set_stat(&mut self, key: &str, value: String) {
+        match self.stats.iter_mut().find(|(k, _)| k == key) {
+            Some(stat) => stat.1 = value,
+            None => self.stats.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.stats.is_empty() && self.log.is_empty()
+    }
+
+    pub fn stats_line(&self) -> Option<String> {
+        if self.stats.is_empty() {
+            return None;
+        }
+        Some(
+            self.stats
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value))
+                .collect::<Vec<String>>()
+                .join("   "),
+        )
+    }
+
+    /// Plain text rendering: title, stats and log messages, one per line
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        lines.extend(self.title.iter().cloned());
+        lines.extend(self.stats_line());
+        lines.extend(
+            self.log
+                .iter()
+                .flat_map(|m| m.lines().map(|l| l.to_string())),
+        );
+        lines
+    }
+
+    /// To be called after the frame is done, clears what's attached to it
+    pub fn end_frame(&mut self) {
+        self.log.clear();
+    }
+}
+
+/// Frame text together with the annotations rendered as plain header lines
+pub struct AnnotatedFrame {
+    pub text: String,
+    pub header: Vec<String>,
+}
+
+impl AnnotatedFrame {
+    pub fn new() -> AnnotatedFrame {
+        AnnotatedFrame {
+            text: String::with_capacity(1000),
+            header: Vec::new(),
+        }
+    }
 }
 
 pub trait Visualizer {
@@ -42,6 +123,16 @@ pub trait Visualizer {
 
     fn end_frame(&mut self);
 
+    /// Called after a solution part is done producing frames
+    fn finish(&mut self) {}
+
+    fn set_title(&mut self, _title: &str) {}
+
+    fn set_stat(&mut self, _key: &str, _value: &dyn Display) {}
+
+    /// Message shown with the current frame
+    fn log(&mut self, _message: &str) {}
+
     fn is_enabled(&self) -> bool;
 
     fn add_char_visualization_option(&mut self, opt: CharVisualizationOption);
@@ -63,26 +154,69 @@ impl Visualizer for DisabledVisualizer {
 
 const INTERACTIVE_TERMINAL_VISUALIZER_MAX_HISTORY_DEPTH: usize = 1000;
 
+const INTERACTIVE_MODE_HELP: &str = "h, l - forward and backwards\n_, $ - jump to the first and last frame\narrows - pan displayed frame portion\nq - exit interactive mode";
+
+/// Puts the terminal back to normal: main screen, visible cursor. Safe to call when
+/// already restored, which is what the Ctrl-C handler relies on.
+fn restore_terminal() {
+    let mut stdout = io::stdout();
+    write!(
+        stdout,
+        "{}{}",
+        termion::screen::ToMainScreen,
+        termion::cursor::Show
+    )
+    .ok();
+    stdout.flush().ok();
+}
+
 pub struct TerminalVisualizer {
     fps: f32,
     is_interactive: bool,
-    prev_displayed_lines: usize,
-    frame_buffer: VecDeque<String>,
+    frame_buffer: VecDeque<AnnotatedFrame>,
+    annotations: FrameAnnotations,
     opts: Vec<CharVisualizationOption>,
+    theme: Theme,
+    is_truecolor: bool,
     top_left_offset: Coords<usize>,
+    is_on_alternate_screen: bool,
+    // what is currently drawn on the alternate screen, used to redraw only changed cells
+    displayed_cells: Vec<Vec<char>>,
+    displayed_terminal_size: (u16, u16),
 }
 
 impl TerminalVisualizer {
-    pub fn new(fps: f32, is_interactive: bool) -> TerminalVisualizer {
+    pub fn new(fps: f32, is_interactive: bool, theme: Theme) -> TerminalVisualizer {
+        // in interactive mode the terminal is raw and Ctrl-C arrives as a key instead. The
+        // handler is process wide, so it's installed for the first visualizer only
+        static CTRLC_HANDLER: Once = Once::new();
+        CTRLC_HANDLER.call_once(|| {
+            if let Err(e) = ctrlc::set_handler(|| {
+                restore_terminal();
+                std::process::exit(130);
+            }) {
+                eprintln!(
+                    "Failed to set the Ctrl-C handler, the terminal may not be restored on exit ({})",
+                    e
+                );
+            }
+        });
         TerminalVisualizer {
             fps,
             is_interactive,
-            prev_displayed_lines: 0,
             frame_buffer: VecDeque::with_capacity(
                 INTERACTIVE_TERMINAL_VISUALIZER_MAX_HISTORY_DEPTH,
             ),
+            annotations: FrameAnnotations::default(),
             opts: Vec::new(),
+            theme,
+            // same check as most terminal apps, there's no reliable way to query it
+            is_truecolor: std::env::var("COLORTERM")
+                .is_ok_and(|v| v == "truecolor" || v == "24bit"),
             top_left_offset: Coords::origin(),
+            is_on_alternate_screen: false,
+            displayed_cells: Vec::new(),
+            displayed_terminal_size: (0, 0),
         }
     }
 }
@@ -91,12 +225,9 @@ impl TerminalVisualizer {
     fn apply_opts(&self, ch: char) -> Option<String> {
         match self.opts.iter().find(|o| o.char == ch) {
             Some(char_vis_opt) => {
+                let color = self.theme.color(char_vis_opt.role);
                 let mut style = ansi_term::Style::new();
-                style = style.fg(ansi_term::Colour::RGB(
-                    char_vis_opt.color[0],
-                    char_vis_opt.color[1],
-                    char_vis_opt.color[2],
-                ));
+                style = style.fg(to_ansi_colour(color, self.is_truecolor));
                 if char_vis_opt.is_bold {
                     style = style.bold();
                 }
@@ -105,178 +236,350 @@ impl TerminalVisualizer {
             None => None,
         }
     }
+
+    fn enter_alternate_screen(&mut self) {
+        if self.is_on_alternate_screen {
+            return;
+        }
+        print!(
+            "{}{}{}",
+            termion::screen::ToAlternateScreen,
+            termion::cursor::Hide,
+            termion::clear::All
+        );
+        self.is_on_alternate_screen = true;
+        self.displayed_cells.clear();
+    }
+
+    fn leave_alternate_screen(&mut self) {
+        if !self.is_on_alternate_screen {
+            return;
+        }
+        restore_terminal();
+        self.is_on_alternate_screen = false;
+        self.displayed_cells.clear();
+    }
+
+    /// Draws cells on the alternate screen, touching only those that differ from
+    /// the previous call
+    fn redraw(&mut self, cells: Vec<Vec<char>>) {
+        let terminal_size = termion::terminal_size().unwrap_or((80, 24));
+        let mut to_print = String::new();
+        if terminal_size != self.displayed_terminal_size {
+            to_print.push_str(termion::clear::All.as_ref());
+            self.displayed_cells.clear();
+            self.displayed_terminal_size = terminal_size;
+        }
+
+        let no_cells: Vec<char> = Vec::new();
+        for y in 0..max(cells.len(), self.displayed_cells.len()) {
+            let new_row = cells.get(y).unwrap_or(&no_cells);
+            let old_row = self.displayed_cells.get(y).unwrap_or(&no_cells);
+            let row_width = max(new_row.len(), old_row.len());
+            let cell_at = |row: &Vec<char>, x: usize| *row.get(x).unwrap_or(&' ');
+
+            let mut x = 0;
+            while x < row_width {
+                if cell_at(new_row, x) == cell_at(old_row, x) {
+                    x += 1;
+                    continue;
+                }
+                // consecutive changed cells are printed after a single cursor jump
+                to_print.push_str(&termion::cursor::Goto(x as u16 + 1, y as u16 + 1).to_string());
+                while x < row_width && cell_at(new_row, x) != cell_at(old_row, x) {
+                    let ch = cell_at(new_row, x);
+                    match self.apply_opts(ch) {
+                        Some(with_opts) => to_print.push_str(&with_opts),
+                        None => to_print.push(ch),
+                    }
+                    x += 1;
+                }
+            }
+        }
+
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", to_print).unwrap();
+        stdout.flush().unwrap();
+        self.displayed_cells = cells;
+    }
+
+    fn write_status_lines(&self, status: &str, first_row: u16) {
+        let mut stdout = io::stdout();
+        for (idx, line) in status.lines().enumerate() {
+            write!(
+                stdout,
+                "{}{}{}",
+                termion::cursor::Goto(1, first_row + idx as u16),
+                termion::clear::CurrentLine,
+                line
+            )
+            .unwrap();
+        }
+        stdout.flush().unwrap();
+    }
 }
 
 impl Visualizer for TerminalVisualizer {
     fn write_char(&mut self, ch: char) {
-        if self.frame_buffer.len() == 0 {
-            self.frame_buffer.push_back(String::with_capacity(1000));
+        if self.frame_buffer.is_empty() {
+            self.frame_buffer.push_back(AnnotatedFrame::new());
         }
         let current_frame_idx = self.frame_buffer.len() - 1;
-        self.frame_buffer[current_frame_idx].push(ch);
+        self.frame_buffer[current_frame_idx].text.push(ch);
     }
 
     fn end_frame(&mut self) {
+        if self.frame_buffer.is_empty() {
+            self.frame_buffer.push_back(AnnotatedFrame::new());
+        }
         let mut displayed_frame_idx = self.frame_buffer.len() - 1;
+        self.frame_buffer[displayed_frame_idx].header = self.annotations.lines();
+        self.annotations.end_frame();
+        self.enter_alternate_screen();
 
         loop {
-            for _ in 0..self.prev_displayed_lines {
-                print!("\x1B[2K\x1B[1A\x1B[2K"); // clear
-            }
-
-            let displayed_frame = &self.frame_buffer[displayed_frame_idx];
-
-            let (terminal_width, terminal_height) = termion::terminal_size().unwrap_or((80, 80));
-            let display_width = terminal_width as usize;
-            let display_height = if terminal_height > 20 {
-                terminal_height as usize - 15
+            let (terminal_width, terminal_height) = termion::terminal_size().unwrap_or((80, 24));
+            let status_height = if self.is_interactive {
+                INTERACTIVE_MODE_HELP.lines().count() + 2
             } else {
-                terminal_height as usize
+                0
             };
-            let displayed_frame_lines: Vec<String> = displayed_frame
-                .lines()
-                .skip(self.top_left_offset.y)
-                .take(display_height)
-                .map(|l| {
-                    l.chars()
-                        .skip(self.top_left_offset.x)
-                        .take(display_width)
-                        .collect()
-                })
-                .collect();
+            let display_width = terminal_width as usize;
+            let display_height = (terminal_height as usize).saturating_sub(status_height);
 
-            let mut to_print: String = String::new();
-            for ch in displayed_frame_lines.join("\n").chars() {
-                if let Some(with_opts) = self.apply_opts(ch) {
-                    to_print.extend(with_opts.chars());
-                } else {
-                    to_print.push(ch);
-                }
+            let displayed_frame = &self.frame_buffer[displayed_frame_idx];
+            // header is not panned together with the frame
+            let mut displayed_cells: Vec<Vec<char>> = displayed_frame
+                .header
+                .iter()
+                .map(|l| l.chars().take(display_width).collect())
+                .collect();
+            if !displayed_cells.is_empty() {
+                displayed_cells.push(repeated_char('─', display_width).chars().collect());
             }
-            print!("{}", to_print);
-
-            self.prev_displayed_lines = displayed_frame_lines.len();
+            let frame_height = display_height.saturating_sub(displayed_cells.len());
+            displayed_cells.extend(
+                displayed_frame
+                    .text
+                    .lines()
+                    .skip(self.top_left_offset.y)
+                    .take(frame_height)
+                    .map(|l| {
+                        l.chars()
+                            .skip(self.top_left_offset.x)
+                            .take(display_width)
+                            .collect()
+                    }),
+            );
+            displayed_cells.truncate(display_height);
+            self.redraw(displayed_cells);
 
             if !self.is_interactive {
                 sleep(Duration::from_micros((1e6 / self.fps) as u64));
                 break;
-            } else {
-                // user controls input loop
-                println!(
-                    "\ninteractive mode (frame {} / {})\nh, l - forward and backwards\n_, $ - jump to the first and last frame\narrows - pan displayed frame portion\nq - exit interactive mode",
+            }
+
+            // user controls input loop
+            let status_first_row = display_height as u16 + 1;
+            self.write_status_lines(
+                &format!(
+                    "\ninteractive mode (frame {} / {})\n{}",
                     displayed_frame_idx + 1,
-                    self.frame_buffer.len()
-                );
-                let stdin = io::stdin();
-                let mut stdout = io::stdout().into_raw_mode().unwrap();
-                let mut generate_next_frame = false;
-                for c in stdin.keys() {
-                    write!(stdout, "{}", termion::clear::CurrentLine).unwrap();
-
-                    match c.unwrap() {
-                        Key::Char('h') => {
-                            if displayed_frame_idx > 0 {
-                                displayed_frame_idx -= 1;
-                                break;
-                            }
-                        }
-                        Key::Char('l') | Key::Char(' ') => {
-                            if displayed_frame_idx < self.frame_buffer.len() - 1 {
-                                displayed_frame_idx += 1;
-                            } else {
-                                generate_next_frame = true;
-                            }
-                            break;
-                        }
-                        Key::Char('$') => {
-                            displayed_frame_idx = self.frame_buffer.len() - 1;
-                            break;
-                        }
-                        Key::Char('_') => {
-                            displayed_frame_idx = 0;
-                            break;
-                        }
-                        Key::Left => {
-                            if self.top_left_offset.x > 0 {
-                                self.top_left_offset.x -= 1;
-                            }
-                            break;
-                        }
-                        Key::Right => {
-                            self.top_left_offset.x += 1;
-                            break;
-                        }
-                        Key::Up => {
-                            if self.top_left_offset.y > 0 {
-                                self.top_left_offset.y -= 1;
-                            }
-                            break;
+                    self.frame_buffer.len(),
+                    INTERACTIVE_MODE_HELP
+                ),
+                status_first_row,
+            );
+            let stdin = io::stdin();
+            let stdout = io::stdout().into_raw_mode().unwrap();
+            let mut generate_next_frame = false;
+            for c in stdin.keys() {
+                match c.unwrap() {
+                    Key::Char('h') if displayed_frame_idx > 0 => {
+                        displayed_frame_idx -= 1;
+                        break;
+                    }
+                    Key::Char('l') | Key::Char(' ') => {
+                        if displayed_frame_idx < self.frame_buffer.len() - 1 {
+                            displayed_frame_idx += 1;
+                        } else {
+                            generate_next_frame = true;
                         }
-                        Key::Down => {
-                            self.top_left_offset.y += 1;
-                            break;
+                        break;
+                    }
+                    Key::Char('$') => {
+                        displayed_frame_idx = self.frame_buffer.len() - 1;
+                        break;
+                    }
+                    Key::Char('_') => {
+                        displayed_frame_idx = 0;
+                        break;
+                    }
+                    Key::Left => {
+                        if self.top_left_offset.x > 0 {
+                            self.top_left_offset.x -= 1;
                         }
-                        Key::Char('q') => {
-                            self.is_interactive = false;
-                            generate_next_frame = true;
-                            break;
+                        break;
+                    }
+                    Key::Right => {
+                        self.top_left_offset.x += 1;
+                        break;
+                    }
+                    Key::Up => {
+                        if self.top_left_offset.y > 0 {
+                            self.top_left_offset.y -= 1;
                         }
-                        _ => {}
+                        break;
+                    }
+                    Key::Down => {
+                        self.top_left_offset.y += 1;
+                        break;
+                    }
+                    Key::Char('q') => {
+                        self.is_interactive = false;
+                        generate_next_frame = true;
+                        break;
+                    }
+                    Key::Ctrl('c') => {
+                        drop(stdout);
+                        restore_terminal();
+                        std::process::exit(130);
                     }
-                    stdout.flush().unwrap();
+                    _ => {}
                 }
+            }
+            drop(stdout);
 
-                for _ in 0..5 {
-                    // clearing prompt
-                    print!("\x1B[2K\x1B[1A\x1B[2K");
-                }
-                if generate_next_frame {
-                    break;
-                }
+            if !self.is_interactive {
+                // the prompt area becomes part of the frame area again
+                let blank_status = "\n".repeat(status_height);
+                self.write_status_lines(&blank_status, status_first_row);
+            }
+            if generate_next_frame {
+                break;
             }
         }
 
         // creating new empty frame for the next iteration
-        self.frame_buffer.push_back(String::with_capacity(1000));
+        self.frame_buffer.push_back(AnnotatedFrame::new());
         if self.frame_buffer.len() > INTERACTIVE_TERMINAL_VISUALIZER_MAX_HISTORY_DEPTH {
             self.frame_buffer.pop_front();
         }
     }
 
+    fn finish(&mut self) {
+        self.annotations = FrameAnnotations::default();
+        if !self.is_on_alternate_screen {
+            return;
+        }
+        self.leave_alternate_screen();
+        // keeping the last frame in the regular scrollback so it's visible after the run
+        let mut to_print = String::new();
+        if let Some(last_frame) = self.frame_buffer.iter().rev().find(|f| !f.text.is_empty()) {
+            for line in last_frame.header.iter() {
+                to_print.push_str(line);
+                to_print.push('\n');
+            }
+            to_print.push_str(&last_frame.text);
+        }
+        let styled_to_print: String = to_print
+            .chars()
+            .map(|ch| self.apply_opts(ch).unwrap_or_else(|| String::from(ch)))
+            .collect();
+        println!("{}", styled_to_print);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.annotations.title = Some(title.to_string());
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        self.annotations.set_stat(key, value.to_string());
+    }
+
+    fn log(&mut self, message: &str) {
+        self.annotations.log.push(message.to_string());
+    }
+
     fn is_enabled(&self) -> bool {
         true
     }
 
     fn add_char_visualization_option(&mut self, opt: CharVisualizationOption) {
         self.opts.push(opt);
+        // cells with this char may already be on the screen unstyled
+        self.displayed_cells.clear();
+        self.displayed_terminal_size = (0, 0);
     }
 }
 
+impl Drop for TerminalVisualizer {
+    fn drop(&mut self) {
+        self.leave_alternate_screen();
+    }
+}
+
+// log messages beyond this count are not shown in the GIF caption
+const GIF_CAPTION_LOG_LINES: usize = 4;
+
 pub struct GifVisualizer {
     fps: f32,
     width_px: u32,
     curr_frame: String,
+    annotations: FrameAnnotations,
     gif: GifEncoder<File>,
     frame_dimensions: Option<(usize, usize)>,
+    caption_height: usize,
+    is_progress_shown: bool,
     frames_since_last_progress_print: u32,
     opts: Vec<CharVisualizationOption>,
+    theme: Theme,
 }
 
 impl GifVisualizer {
-    pub fn new(filename: &str, fps: f32, width_px: u32) -> GifVisualizer {
+    pub fn new(filename: &str, fps: f32, width_px: u32, theme: Theme) -> GifVisualizer {
         GifVisualizer {
             fps,
             width_px,
             curr_frame: String::new(),
+            annotations: FrameAnnotations::default(),
             gif: GifEncoder::new_with_speed(
-                File::create(filename).expect(&format!("Failed to create file: {}", filename)),
+                File::create(filename)
+                    .unwrap_or_else(|_| panic!("Failed to create file: {}", filename)),
                 20,
             ),
             frame_dimensions: None,
+            caption_height: 0,
+            is_progress_shown: true,
             frames_since_last_progress_print: 0,
             opts: Vec::new(),
+            theme,
         }
     }
+
+    pub fn without_progress(mut self) -> GifVisualizer {
+        self.is_progress_shown = false;
+        self
+    }
+
+    fn caption_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        lines.extend(self.annotations.title.iter().cloned());
+        lines.extend(self.annotations.stats_line());
+        let log_lines: Vec<&str> = self
+            .annotations
+            .log
+            .iter()
+            .flat_map(|m| m.lines())
+            .collect();
+        lines.extend(
+            log_lines
+                .iter()
+                .skip(log_lines.len().saturating_sub(GIF_CAPTION_LOG_LINES))
+                .map(|l| l.to_string()),
+        );
+        lines
+    }
 }
 
 impl Visualizer for GifVisualizer {
@@ -286,13 +589,36 @@ impl Visualizer for GifVisualizer {
 
     fn end_frame(&mut self) {
         let mut frame_chars = CharMatrix::new(&self.curr_frame);
-        if let Some(dims) = self.frame_dimensions {
-            frame_chars.ensure_dimensions(dims.0, dims.1);
-        } else {
-            self.frame_dimensions = Some(frame_chars.dimensions());
+        let mut caption_chars = CharMatrix::new(&self.caption_lines().join("\n"));
+        if self.frame_dimensions.is_none() {
+            // all frames in GIF have the same size, so the caption strip gets room for log
+            // messages even if the first frame doesn't have any
+            let (frame_width, frame_height) = frame_chars.dimensions();
+            let (caption_width, _) = caption_chars.dimensions();
+            self.frame_dimensions = Some((max(frame_width, caption_width), frame_height));
+            if !self.annotations.is_empty() {
+                self.caption_height = self.annotations.title.iter().count()
+                    + self.annotations.stats_line().iter().count()
+                    + GIF_CAPTION_LOG_LINES;
+            }
         }
-
-        let image = text_to_image(&frame_chars, self.width_px, 1.0, 0.0, &self.opts);
+        let (width, height) = self.frame_dimensions.unwrap();
+        frame_chars.ensure_dimensions(width, height);
+        if self.caption_height > 0 {
+            caption_chars.ensure_dimensions(width, self.caption_height);
+            frame_chars.append(CharMatrix::new(&repeated_char('─', width)));
+            frame_chars.append(caption_chars);
+        }
+        self.annotations.end_frame();
+
+        let image = text_to_image(
+            &frame_chars,
+            self.width_px,
+            1.0,
+            0.0,
+            &self.opts,
+            self.theme,
+        );
         self.curr_frame.clear();
         if let Some(img) = image {
             let frame =
@@ -303,13 +629,29 @@ impl Visualizer for GifVisualizer {
         }
 
         self.frames_since_last_progress_print += 1;
-        if self.frames_since_last_progress_print as f32 > self.fps {
+        if self.is_progress_shown && self.frames_since_last_progress_print as f32 > self.fps {
             print!(".");
             io::stdout().flush().unwrap();
             self.frames_since_last_progress_print = 0;
         }
     }
 
+    fn finish(&mut self) {
+        self.annotations = FrameAnnotations::default();
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.annotations.title = Some(title.to_string());
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        self.annotations.set_stat(key, value.to_string());
+    }
+
+    fn log(&mut self, message: &str) {
+        self.annotations.log.push(message.to_string());
+    }
+
     fn is_enabled(&self) -> bool {
         true
     }
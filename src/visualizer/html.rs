@@ -0,0 +1,274 @@
+use std::{fmt::Display, fs, io};
+
+use image::Rgb;
+
+use super::{AnnotatedFrame, CharVisualizationOption, FrameAnnotations, Visualizer};
+use crate::theme::Theme;
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>AoC 2022 visualization</title>
+<style>
+  body { background: /*BACKGROUND*/; color: /*FOREGROUND*/; font-family: monospace; margin: 1em; }
+  #controls { margin-bottom: 1em; display: flex; gap: 1em; align-items: center; }
+  #slider { flex-grow: 1; }
+  #header { color: #999; white-space: pre; margin: 0; }
+  #frame { line-height: 1.1; margin-top: 0.5em; }
+  button { font-family: monospace; min-width: 5em; }
+</style>
+</head>
+<body>
+<div id="controls">
+  <button id="play">play</button>
+  <input id="slider" type="range" min="0" value="0">
+  <span id="counter"></span>
+  <label>speed <select id="speed">
+    <option value="0.25">x0.25</option>
+    <option value="0.5">x0.5</option>
+    <option value="1" selected>x1</option>
+    <option value="2">x2</option>
+    <option value="4">x4</option>
+    <option value="10">x10</option>
+  </select></label>
+</div>
+<pre id="header"></pre>
+<pre id="frame"></pre>
+<script>
+const FRAMES = /*FRAMES*/;
+const CHAR_OPTS = /*CHAR_OPTS*/;
+const FPS = /*FPS*/;
+
+const slider = document.getElementById("slider");
+const counter = document.getElementById("counter");
+const playButton = document.getElementById("play");
+const speedSelect = document.getElementById("speed");
+const header = document.getElementById("header");
+const frame = document.getElementById("frame");
+
+function escapeChar(ch) {
+  return ch === "<" ? "&lt;" : ch === ">" ? "&gt;" : ch === "&" ? "&amp;" : ch;
+}
+
+function renderText(text) {
+  let html = "";
+  for (const ch of text) {
+    const opt = CHAR_OPTS[ch];
+    if (opt === undefined) {
+      html += escapeChar(ch);
+    } else {
+      const weight = opt.bold ? "font-weight:bold;" : "";
+      html += `<span style="color:${opt.color};${weight}">${escapeChar(ch)}</span>`;
+    }
+  }
+  return html;
+}
+
+function show(idx) {
+  slider.value = idx;
+  counter.textContent = `frame ${idx + 1} / ${FRAMES.length}`;
+  header.textContent = FRAMES[idx].header.join("\n");
+  frame.innerHTML = renderText(FRAMES[idx].text);
+}
+
+let timer = null;
+function stop() {
+  clearInterval(timer);
+  timer = null;
+  playButton.textContent = "play";
+}
+function play() {
+  if (Number(slider.value) >= FRAMES.length - 1) {
+    show(0);
+  }
+  playButton.textContent = "pause";
+  timer = setInterval(() => {
+    const next = Number(slider.value) + 1;
+    if (next >= FRAMES.length) {
+      stop();
+    } else {
+      show(next);
+    }
+  }, 1000 / (FPS * Number(speedSelect.value)));
+}
+
+playButton.onclick = () => (timer === null ? play() : stop());
+speedSelect.onchange = () => {
+  if (timer !== null) {
+    stop();
+    play();
+  }
+};
+slider.oninput = () => show(Number(slider.value));
+document.onkeydown = (e) => {
+  if (e.key === "h" || e.key === "ArrowLeft") {
+    show(Math.max(Number(slider.value) - 1, 0));
+  } else if (e.key === "l" || e.key === "ArrowRight") {
+    show(Math.min(Number(slider.value) + 1, FRAMES.length - 1));
+  } else if (e.key === " ") {
+    e.preventDefault();
+    playButton.onclick();
+  }
+};
+
+slider.max = Math.max(FRAMES.length - 1, 0);
+if (FRAMES.length > 0) {
+  show(0);
+}
+</script>
+</body>
+</html>
+"#;
+
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            // keeps "</script>" inside of frames from closing the script tag
+            '<' => res.push_str("\\u003c"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+pub(super) fn frame_json(frame: &AnnotatedFrame) -> String {
+    let header: Vec<String> = frame.header.iter().map(|l| json_string(l)).collect();
+    format!(
+        "{{\"header\":[{}],\"text\":{}}}",
+        header.join(","),
+        json_string(&frame.text)
+    )
+}
+
+fn css_color(color: Rgb<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Fills in page colors placeholders of viewer pages
+pub(super) fn apply_theme(page: &str, theme: Theme) -> String {
+    page.replace("/*BACKGROUND*/", &css_color(theme.background()))
+        .replace("/*FOREGROUND*/", &css_color(theme.foreground()))
+}
+
+pub(super) fn char_opts_json(opts: &[CharVisualizationOption], theme: Theme) -> String {
+    let opts: Vec<String> = opts
+        .iter()
+        .map(|o| {
+            format!(
+                "{}:{{\"color\":\"{}\",\"bold\":{}}}",
+                json_string(&o.char.to_string()),
+                css_color(theme.color(o.role)),
+                o.is_bold
+            )
+        })
+        .collect();
+    format!("{{{}}}", opts.join(","))
+}
+
+/// Writes all frames into a single self-contained HTML file with a small player, so that
+/// the visualization can be shared and scrubbed through in a browser
+pub struct HtmlVisualizer {
+    filename: String,
+    fps: f32,
+    frames: Vec<AnnotatedFrame>,
+    curr_frame: String,
+    annotations: FrameAnnotations,
+    opts: Vec<CharVisualizationOption>,
+    theme: Theme,
+    is_written: bool,
+}
+
+impl HtmlVisualizer {
+    pub fn new(filename: &str, fps: f32, theme: Theme) -> HtmlVisualizer {
+        HtmlVisualizer {
+            filename: filename.to_string(),
+            fps,
+            frames: Vec::new(),
+            curr_frame: String::new(),
+            annotations: FrameAnnotations::default(),
+            opts: Vec::new(),
+            theme,
+            is_written: true,
+        }
+    }
+
+    fn write(&mut self) -> io::Result<()> {
+        let frames: Vec<String> = self.frames.iter().map(frame_json).collect();
+        let frames_json = format!("[\n{}\n]", frames.join(",\n"));
+        let html = apply_theme(HTML_TEMPLATE, self.theme)
+            .replace("/*FRAMES*/", &frames_json)
+            .replace("/*CHAR_OPTS*/", &char_opts_json(&self.opts, self.theme))
+            .replace("/*FPS*/", &self.fps.to_string());
+        fs::write(&self.filename, html)?;
+        self.is_written = true;
+        Ok(())
+    }
+}
+
+impl Visualizer for HtmlVisualizer {
+    fn write_char(&mut self, ch: char) {
+        self.curr_frame.push(ch);
+    }
+
+    fn end_frame(&mut self) {
+        self.frames.push(AnnotatedFrame {
+            text: std::mem::take(&mut self.curr_frame),
+            header: self.annotations.lines(),
+        });
+        self.annotations.end_frame();
+        self.is_written = false;
+    }
+
+    fn finish(&mut self) {
+        // rewriting the whole file after each part, so it's usable even if the next one is interrupted
+        self.annotations = FrameAnnotations::default();
+        if !self.is_written {
+            let written = self.write();
+            // not retried on drop
+            self.is_written = true;
+            written.unwrap_or_else(|e| panic!("Failed to write file: {} ({})", self.filename, e));
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.annotations.title = Some(title.to_string());
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        self.annotations.set_stat(key, value.to_string());
+    }
+
+    fn log(&mut self, message: &str) {
+        self.annotations.log.push(message.to_string());
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn add_char_visualization_option(&mut self, opt: CharVisualizationOption) {
+        self.opts.push(opt);
+    }
+}
+
+/// Frames after the last `finish` are still saved, e.g. when the solution panics midway;
+/// failing to write them is only reported, as panicking while unwinding would abort
+impl Drop for HtmlVisualizer {
+    fn drop(&mut self) {
+        if !self.is_written {
+            if let Err(e) = self.write() {
+                eprintln!("Failed to write file: {} ({})", self.filename, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use super::{CharVisualizationOption, Visualizer};
+use crate::theme::Role;
+
+/// Line-based recording format, one record per line:
+///
+/// aocvis 1                       header
+/// opt <bold 0|1> <role> <char>   char visualization option, role as in theme::Role
+/// title <text>
+/// stat <key>\t<value>
+/// log <text>
+/// frame <msec> <line count>      followed by this many lines of frame text
+/// repeat <msec>                  same frame text as the previous one
+/// finish                         solution part is done
+///
+/// where msec is time since the recording start
+const RECORDING_HEADER: &str = "aocvis 1";
+
+/// Writes everything the solution shows into a file that can be replayed later with any backend
+pub struct RecordingVisualizer {
+    out: BufWriter<File>,
+    started_at: Instant,
+    curr_frame: String,
+    prev_frame: Option<String>,
+}
+
+impl RecordingVisualizer {
+    pub fn new(filename: &str) -> RecordingVisualizer {
+        let file = File::create(filename)
+            .unwrap_or_else(|_| panic!("Failed to create file: {}", filename));
+        let mut out = BufWriter::new(file);
+        writeln!(out, "{}", RECORDING_HEADER).expect("Error writing recording");
+        RecordingVisualizer {
+            out,
+            started_at: Instant::now(),
+            curr_frame: String::new(),
+            prev_frame: None,
+        }
+    }
+
+    fn write_record(&mut self, record: &str) {
+        writeln!(self.out, "{}", record).expect("Error writing recording");
+    }
+}
+
+impl Visualizer for RecordingVisualizer {
+    fn write_char(&mut self, ch: char) {
+        self.curr_frame.push(ch);
+    }
+
+    fn end_frame(&mut self) {
+        let msec = self.started_at.elapsed().as_millis();
+        let frame = std::mem::take(&mut self.curr_frame);
+        if self.prev_frame.as_ref() == Some(&frame) {
+            self.write_record(&format!("repeat {}", msec));
+        } else {
+            self.write_record(&format!("frame {} {}", msec, frame.lines().count()));
+            for line in frame.lines() {
+                self.write_record(line);
+            }
+            self.prev_frame = Some(frame);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.write_record("finish");
+        self.out.flush().expect("Error writing recording");
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.write_record(&format!("title {}", title.replace('\n', " ")));
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        self.write_record(&format!(
+            "stat {}\t{}",
+            key.replace(['\t', '\n'], " "),
+            value.to_string().replace('\n', " ")
+        ));
+    }
+
+    fn log(&mut self, message: &str) {
+        for line in message.lines() {
+            self.write_record(&format!("log {}", line));
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn add_char_visualization_option(&mut self, opt: CharVisualizationOption) {
+        self.write_record(&format!(
+            "opt {} {} {}",
+            opt.is_bold as u8, opt.role, opt.char
+        ));
+    }
+}
+
+fn invalid_record(line_number: usize, line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid record at line {}: {}", line_number, line),
+    )
+}
+
+/// Feeds a recording into a visualizer. With `original_timing`, frames are shown with the
+/// same delays as they were produced by the solution.
+pub fn replay(filename: &str, vis: &mut dyn Visualizer, original_timing: bool) -> io::Result<()> {
+    let reader = BufReader::new(File::open(filename)?);
+    let mut lines = reader.lines().enumerate();
+
+    match lines.next() {
+        Some((_, Ok(header))) if header == RECORDING_HEADER => {}
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a visualization recording", filename),
+            ))
+        }
+    }
+
+    let replay_started_at = Instant::now();
+    let wait_until_msec = |msec: u64| {
+        if original_timing {
+            let target = Duration::from_millis(msec);
+            let elapsed = replay_started_at.elapsed();
+            if target > elapsed {
+                sleep(target - elapsed);
+            }
+        }
+    };
+
+    let mut prev_frame = String::new();
+    while let Some((idx, line)) = lines.next() {
+        let line = line?;
+        let line_number = idx + 1;
+        let (record_type, record) = line.split_once(' ').unwrap_or((&line, ""));
+        match record_type {
+            "opt" => {
+                let mut parts = record.splitn(3, ' ');
+                let is_bold = parts.next() == Some("1");
+                let role = parts
+                    .next()
+                    .and_then(|r| r.parse::<Role>().ok())
+                    .ok_or_else(|| invalid_record(line_number, &line))?;
+                let char = parts
+                    .next()
+                    .and_then(|c| c.chars().next())
+                    .ok_or_else(|| invalid_record(line_number, &line))?;
+                vis.add_char_visualization_option(CharVisualizationOption {
+                    char,
+                    is_bold,
+                    role,
+                });
+            }
+            "title" => vis.set_title(record),
+            "stat" => {
+                let (key, value) = record
+                    .split_once('\t')
+                    .ok_or_else(|| invalid_record(line_number, &line))?;
+                vis.set_stat(key, &value);
+            }
+            "log" => vis.log(record),
+            "frame" => {
+                let (msec, line_count) = record
+                    .split_once(' ')
+                    .and_then(|(m, c)| Some((m.parse::<u64>().ok()?, c.parse::<usize>().ok()?)))
+                    .ok_or_else(|| invalid_record(line_number, &line))?;
+                prev_frame.clear();
+                for _ in 0..line_count {
+                    let (_, frame_line) = lines
+                        .next()
+                        .ok_or_else(|| invalid_record(line_number, &line))?;
+                    prev_frame.push_str(&frame_line?);
+                    prev_frame.push('\n');
+                }
+                wait_until_msec(msec);
+                vis.write_str(&prev_frame);
+                vis.end_frame();
+            }
+            "repeat" => {
+                let msec = record
+                    .parse::<u64>()
+                    .map_err(|_| invalid_record(line_number, &line))?;
+                wait_until_msec(msec);
+                vis.write_str(&prev_frame);
+                vis.end_frame();
+            }
+            "finish" => vis.finish(),
+            _ => return Err(invalid_record(line_number, &line)),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use super::{CharVisualizationOption, Visualizer};
+
+/// Which of the frames produced by a solution are actually passed to the visualizer.
+/// Frames are numbered from 1, like in the interactive terminal mode.
+pub struct FrameSampling {
+    pub every: usize,
+    pub max_frames: Option<usize>,
+    pub from_frame: usize,
+    pub to_frame: Option<usize>,
+    pub min_interval: Option<Duration>,
+}
+
+impl FrameSampling {
+    pub fn is_trivial(&self) -> bool {
+        self.every <= 1
+            && self.max_frames.is_none()
+            && self.from_frame <= 1
+            && self.to_frame.is_none()
+            && self.min_interval.is_none()
+    }
+}
+
+/// Wraps another visualizer and forwards only sampled frames to it, so that solutions don't
+/// have to throttle their output by hand
+pub struct SamplingVisualizer {
+    inner: Box<dyn Visualizer>,
+    sampling: FrameSampling,
+    frame_number: usize,
+    frames_kept: usize,
+    last_kept_at: Option<Instant>,
+    is_current_frame_kept: Option<bool>,
+    // dropped frame is kept around to be shown at the end of the run, so the final state is never lost
+    last_dropped_frame: Option<String>,
+}
+
+impl SamplingVisualizer {
+    pub fn new(inner: Box<dyn Visualizer>, sampling: FrameSampling) -> SamplingVisualizer {
+        SamplingVisualizer {
+            inner,
+            sampling,
+            frame_number: 1,
+            frames_kept: 0,
+            last_kept_at: None,
+            is_current_frame_kept: None,
+            last_dropped_frame: None,
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.sampling
+            .max_frames
+            .is_some_and(|max_frames| self.frames_kept >= max_frames)
+            || self
+                .sampling
+                .to_frame
+                .is_some_and(|to_frame| self.frame_number > to_frame)
+    }
+
+    fn is_sampled(&self) -> bool {
+        if self.is_exhausted() || self.frame_number < self.sampling.from_frame {
+            return false;
+        }
+        let from_frame = self.sampling.from_frame.max(1);
+        if !(self.frame_number - from_frame).is_multiple_of(self.sampling.every.max(1)) {
+            return false;
+        }
+        match (self.sampling.min_interval, self.last_kept_at) {
+            (Some(min_interval), Some(last_kept_at)) => last_kept_at.elapsed() >= min_interval,
+            _ => true,
+        }
+    }
+
+    fn is_current_frame_kept(&mut self) -> bool {
+        match self.is_current_frame_kept {
+            Some(is_kept) => is_kept,
+            None => {
+                let is_kept = self.is_sampled();
+                self.is_current_frame_kept = Some(is_kept);
+                if !is_kept {
+                    self.last_dropped_frame = Some(String::new());
+                }
+                is_kept
+            }
+        }
+    }
+}
+
+impl Visualizer for SamplingVisualizer {
+    fn write_char(&mut self, ch: char) {
+        if self.is_current_frame_kept() {
+            self.inner.write_char(ch);
+        } else if let Some(dropped_frame) = self.last_dropped_frame.as_mut() {
+            dropped_frame.push(ch);
+        }
+    }
+
+    fn end_frame(&mut self) {
+        if self.is_current_frame_kept() {
+            self.inner.end_frame();
+            self.frames_kept += 1;
+            self.last_kept_at = Some(Instant::now());
+            self.last_dropped_frame = None;
+        }
+        self.frame_number += 1;
+        self.is_current_frame_kept = None;
+    }
+
+    fn finish(&mut self) {
+        if let Some(dropped_frame) = self.last_dropped_frame.take() {
+            if !self.is_exhausted() && self.frame_number > self.sampling.from_frame {
+                self.inner.write_str(&dropped_frame);
+                self.inner.end_frame();
+            }
+        }
+        self.inner.finish();
+        // sampling starts over for the next part
+        self.frame_number = 1;
+        self.frames_kept = 0;
+        self.last_kept_at = None;
+        self.is_current_frame_kept = None;
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.inner.set_title(title);
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        self.inner.set_stat(key, value);
+    }
+
+    fn log(&mut self, message: &str) {
+        if self.is_current_frame_kept() {
+            self.inner.log(message);
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.inner.is_enabled() && !self.is_exhausted()
+    }
+
+    fn add_char_visualization_option(&mut self, opt: CharVisualizationOption) {
+        self.inner.add_char_visualization_option(opt);
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+use super::{
+    html::{apply_theme, char_opts_json, frame_json},
+    AnnotatedFrame, CharVisualizationOption, FrameAnnotations, Visualizer,
+};
+use crate::theme::Theme;
+
+// older frames are dropped, a viewer that falls behind skips to the oldest one still kept
+const SERVE_VISUALIZER_MAX_HISTORY_DEPTH: usize = 1000;
+
+const VIEWER_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>AoC 2022 live visualization</title>
+<style>
+  body { background: /*BACKGROUND*/; color: /*FOREGROUND*/; font-family: monospace; margin: 1em; }
+  #controls { margin-bottom: 1em; display: flex; gap: 1em; align-items: center; }
+  #slider { flex-grow: 1; }
+  #header { color: #999; white-space: pre; margin: 0; }
+  #frame { line-height: 1.1; margin-top: 0.5em; }
+</style>
+</head>
+<body>
+<div id="controls">
+  <label><input id="live" type="checkbox" checked> live</label>
+  <input id="slider" type="range" min="0" max="0" value="0">
+  <span id="counter">waiting for frames...</span>
+  <span id="status"></span>
+</div>
+<pre id="header"></pre>
+<pre id="frame"></pre>
+<script>
+const FPS = /*FPS*/;
+const MAX_FRAMES = /*MAX_FRAMES*/;
+
+const live = document.getElementById("live");
+const slider = document.getElementById("slider");
+const counter = document.getElementById("counter");
+const status = document.getElementById("status");
+const header = document.getElementById("header");
+const frame = document.getElementById("frame");
+
+let charOpts = {};
+let frames = [];
+let firstFrameId = 0;
+
+function escapeChar(ch) {
+  return ch === "<" ? "&lt;" : ch === ">" ? "&gt;" : ch === "&" ? "&amp;" : ch;
+}
+
+function renderText(text) {
+  let html = "";
+  for (const ch of text) {
+    const opt = charOpts[ch];
+    if (opt === undefined) {
+      html += escapeChar(ch);
+    } else {
+      const weight = opt.bold ? "font-weight:bold;" : "";
+      html += `<span style="color:${opt.color};${weight}">${escapeChar(ch)}</span>`;
+    }
+  }
+  return html;
+}
+
+function show(idx) {
+  if (frames.length === 0) {
+    return;
+  }
+  slider.max = frames.length - 1;
+  slider.value = idx;
+  counter.textContent = `frame ${firstFrameId + idx + 1} / ${firstFrameId + frames.length}`;
+  header.textContent = frames[idx].header.join("\n");
+  frame.innerHTML = renderText(frames[idx].text);
+}
+
+// live view is redrawn at most FPS times per second, no matter how fast frames arrive
+let redrawScheduled = false;
+function scheduleLiveRedraw() {
+  if (redrawScheduled) {
+    return;
+  }
+  redrawScheduled = true;
+  setTimeout(() => {
+    redrawScheduled = false;
+    if (live.checked) {
+      show(frames.length - 1);
+    } else {
+      slider.max = frames.length - 1;
+    }
+  }, 1000 / FPS);
+}
+
+const events = new EventSource("/events");
+events.addEventListener("opts", (e) => {
+  charOpts = JSON.parse(e.data);
+  scheduleLiveRedraw();
+});
+events.addEventListener("frame", (e) => {
+  const id = Number(e.lastEventId);
+  if (frames.length === 0) {
+    firstFrameId = id;
+  }
+  frames.push(JSON.parse(e.data));
+  if (frames.length > MAX_FRAMES) {
+    frames.shift();
+    firstFrameId += 1;
+  }
+  scheduleLiveRedraw();
+});
+events.onopen = () => (status.textContent = "(connected)");
+events.onerror = () => (status.textContent = "(disconnected)");
+
+slider.oninput = () => {
+  live.checked = false;
+  show(Number(slider.value));
+};
+live.onchange = () => scheduleLiveRedraw();
+</script>
+</body>
+</html>
+"#;
+
+struct ServerState {
+    frames: VecDeque<String>,
+    // absolute index of the first frame still kept
+    first_frame_id: usize,
+    char_opts: String,
+    char_opts_version: usize,
+}
+
+type SharedState = Arc<(Mutex<ServerState>, Condvar)>;
+
+/// Frames are sent from the one after `last_event_id` on, which an EventSource reconnecting
+/// after a dropped connection sets to the last frame it got
+fn handle_events(
+    mut stream: TcpStream,
+    state: SharedState,
+    last_event_id: Option<usize>,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+    let (lock, new_data) = &*state;
+    let mut next_frame_id: usize = last_event_id.map_or(0, |id| id + 1);
+    let mut sent_char_opts_version: usize = 0;
+    loop {
+        let mut to_send = String::new();
+        {
+            let mut state = lock.lock().unwrap();
+            while next_frame_id >= state.first_frame_id + state.frames.len()
+                && sent_char_opts_version == state.char_opts_version
+            {
+                state = new_data.wait(state).unwrap();
+            }
+            if sent_char_opts_version != state.char_opts_version {
+                to_send.push_str(&format!("event: opts\ndata: {}\n\n", state.char_opts));
+                sent_char_opts_version = state.char_opts_version;
+            }
+            next_frame_id = next_frame_id.max(state.first_frame_id);
+            for (idx, frame) in state
+                .frames
+                .iter()
+                .enumerate()
+                .skip(next_frame_id - state.first_frame_id)
+            {
+                to_send.push_str(&format!(
+                    "event: frame\nid: {}\ndata: {}\n\n",
+                    state.first_frame_id + idx,
+                    frame
+                ));
+            }
+            next_frame_id = state.first_frame_id + state.frames.len();
+        }
+        stream.write_all(to_send.as_bytes())?;
+        stream.flush()?;
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, state: SharedState, viewer_page: &str) {
+    let mut request_line = String::new();
+    let mut reader = BufReader::new(&stream);
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // only the frame a reconnecting viewer got last matters from the rest of the request, but
+    // all of it has to be read before responding
+    let mut last_event_id = None;
+    let mut header_line = String::new();
+    while reader.read_line(&mut header_line).is_ok_and(|n| n > 2) {
+        if let Some((name, value)) = header_line.split_once(':') {
+            if name.eq_ignore_ascii_case("Last-Event-ID") {
+                last_event_id = value.trim().parse().ok();
+            }
+        }
+        header_line.clear();
+    }
+
+    let path = request_line.split(' ').nth(1).unwrap_or("/");
+    let result = match path {
+        "/events" => handle_events(stream, state, last_event_id),
+        "/" | "/index.html" => write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            viewer_page.len(),
+            viewer_page
+        ),
+        _ => write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ),
+    };
+    // viewer closing the tab is not an error worth reporting
+    result.ok();
+}
+
+/// Streams frames to browsers over server-sent events on 127.0.0.1, so that long-running
+/// solvers can be watched from another tab or through an SSH tunnel
+pub struct ServeVisualizer {
+    state: SharedState,
+    curr_frame: AnnotatedFrame,
+    annotations: FrameAnnotations,
+    opts: Vec<CharVisualizationOption>,
+    theme: Theme,
+}
+
+impl ServeVisualizer {
+    /// Blocks forever, so that viewers can still look at the frames after the solver is done
+    pub fn wait() -> ! {
+        println!("Visualization finished, still serving (Ctrl-C to exit)");
+        loop {
+            thread::park();
+        }
+    }
+
+    pub fn new(port: u16, fps: f32, theme: Theme) -> ServeVisualizer {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .unwrap_or_else(|e| panic!("Failed to listen on 127.0.0.1:{} ({})", port, e));
+        let port = listener.local_addr().map(|a| a.port()).unwrap_or(port);
+        println!("Serving visualization at http://127.0.0.1:{}/", port);
+
+        let state: SharedState = Arc::new((
+            Mutex::new(ServerState {
+                frames: VecDeque::with_capacity(SERVE_VISUALIZER_MAX_HISTORY_DEPTH),
+                first_frame_id: 0,
+                char_opts: "{}".to_string(),
+                char_opts_version: 0,
+            }),
+            Condvar::new(),
+        ));
+        let viewer_page: &'static str = Box::leak(
+            apply_theme(VIEWER_PAGE, theme)
+                .replace("/*FPS*/", &fps.to_string())
+                .replace(
+                    "/*MAX_FRAMES*/",
+                    &SERVE_VISUALIZER_MAX_HISTORY_DEPTH.to_string(),
+                )
+                .into_boxed_str(),
+        );
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let connection_state = server_state.clone();
+                thread::spawn(move || handle_connection(stream, connection_state, viewer_page));
+            }
+        });
+
+        ServeVisualizer {
+            state,
+            curr_frame: AnnotatedFrame::new(),
+            annotations: FrameAnnotations::default(),
+            opts: Vec::new(),
+            theme,
+        }
+    }
+}
+
+impl Visualizer for ServeVisualizer {
+    fn write_char(&mut self, ch: char) {
+        self.curr_frame.text.push(ch);
+    }
+
+    fn end_frame(&mut self) {
+        self.curr_frame.header = self.annotations.lines();
+        self.annotations.end_frame();
+        let frame = frame_json(&self.curr_frame);
+        self.curr_frame = AnnotatedFrame::new();
+
+        let (lock, new_data) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.frames.push_back(frame);
+        if state.frames.len() > SERVE_VISUALIZER_MAX_HISTORY_DEPTH {
+            state.frames.pop_front();
+            state.first_frame_id += 1;
+        }
+        new_data.notify_all();
+    }
+
+    fn finish(&mut self) {
+        self.annotations = FrameAnnotations::default();
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.annotations.title = Some(title.to_string());
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        self.annotations.set_stat(key, value.to_string());
+    }
+
+    fn log(&mut self, message: &str) {
+        self.annotations.log.push(message.to_string());
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn add_char_visualization_option(&mut self, opt: CharVisualizationOption) {
+        self.opts.push(opt);
+        let (lock, new_data) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.char_opts = char_opts_json(&self.opts, self.theme);
+        state.char_opts_version += 1;
+        new_data.notify_all();
+    }
+}
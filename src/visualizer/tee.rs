@@ -0,0 +1,70 @@
+use std::fmt::Display;
+
+use super::{CharVisualizationOption, Visualizer};
+
+/// Forwards everything to several visualizers, so one run can feed all the outputs at once
+pub struct TeeVisualizer {
+    children: Vec<Box<dyn Visualizer>>,
+}
+
+impl TeeVisualizer {
+    pub fn new(children: Vec<Box<dyn Visualizer>>) -> TeeVisualizer {
+        TeeVisualizer { children }
+    }
+}
+
+impl Visualizer for TeeVisualizer {
+    fn write_char(&mut self, ch: char) {
+        for child in self.children.iter_mut() {
+            child.write_char(ch);
+        }
+    }
+
+    fn write_str(&mut self, s: &str) {
+        for child in self.children.iter_mut() {
+            child.write_str(s);
+        }
+    }
+
+    fn end_frame(&mut self) {
+        for child in self.children.iter_mut() {
+            child.end_frame();
+        }
+    }
+
+    fn finish(&mut self) {
+        for child in self.children.iter_mut() {
+            child.finish();
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        for child in self.children.iter_mut() {
+            child.set_title(title);
+        }
+    }
+
+    fn set_stat(&mut self, key: &str, value: &dyn Display) {
+        // formatting once, the value may be expensive to display
+        let value = value.to_string();
+        for child in self.children.iter_mut() {
+            child.set_stat(key, &value);
+        }
+    }
+
+    fn log(&mut self, message: &str) {
+        for child in self.children.iter_mut() {
+            child.log(message);
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.children.iter().any(|c| c.is_enabled())
+    }
+
+    fn add_char_visualization_option(&mut self, opt: CharVisualizationOption) {
+        for child in self.children.iter_mut() {
+            child.add_char_visualization_option(opt.clone());
+        }
+    }
+}